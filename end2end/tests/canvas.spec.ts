@@ -0,0 +1,224 @@
+import { test, expect } from "@playwright/test";
+
+test("clicking the cube increments the counter", async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+
+  await expect(page.getByText("Cube clicks: 0")).toBeVisible();
+
+  // The frame rate is only reported once the Bevy app is running.
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  await page.locator("canvas").click();
+
+  await expect(page.getByText("Cube clicks: 1")).toBeVisible();
+});
+
+test("the canvas keeps the id it was rendered with on the server", async ({ page, request }) => {
+  // Earlier requests must not change the ids of later pages.
+  await request.get("http://localhost:3000/canvas");
+  const html = await (await request.get("http://localhost:3000/canvas")).text();
+  const id = html.match(/<canvas id="([^"]+)"/)?.[1];
+  expect(id).toBeTruthy();
+
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+  await expect(page.locator(`canvas#${id}`)).toHaveCount(1);
+});
+
+test("keys move the cube only while the canvas is focused", async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  const canvas = page.locator("canvas");
+  const text = page.locator('input[type="text"]');
+
+  // Typing into the page leaves the cube where it is, in the middle of the canvas.
+  await text.click();
+  await page.keyboard.down("d");
+  await page.waitForTimeout(1000);
+  await page.keyboard.up("d");
+  await canvas.click();
+  await expect(page.getByText("Cube clicks: 1")).toBeVisible();
+  await expect(canvas).toBeFocused();
+
+  // With the canvas focused it moves out of the middle.
+  await page.keyboard.down("d");
+  await page.waitForTimeout(1000);
+  await page.keyboard.up("d");
+  await canvas.click();
+  await page.waitForTimeout(500);
+  await expect(page.getByText("Cube clicks: 1")).toBeVisible();
+});
+
+test("selecting the cube shows its transform", async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  const panel = page.locator(".transform-panel");
+  await expect(panel).toContainText("Select an object to transform it.");
+
+  await page.locator("canvas").click();
+
+  const position = panel.getByRole("row", { name: /Position/ });
+  await expect(position).toHaveText(/0\.00\s*0\.50\s*0\.00/);
+  await expect(panel.getByRole("button", { name: "Move" })).toHaveClass("active");
+});
+
+test("the inspector edits the selected entity", async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  const inspector = page.locator(".inspector");
+  await expect(inspector.locator(".entity-tree li").first()).toBeVisible();
+
+  await page.locator("canvas").click();
+
+  const transform = inspector.locator("fieldset").filter({ hasText: "Transform" });
+  await expect(transform.getByLabel("translation.y")).toHaveValue("0.5");
+  await expect(inspector.locator("legend", { hasText: "MeshMaterial3d" })).toBeVisible();
+
+  await transform.getByLabel("translation.x").fill("2");
+  await transform.getByLabel("translation.x").press("Enter");
+
+  const position = page.locator(".transform-panel").getByRole("row", { name: /Position/ });
+  await expect(position).toHaveText(/2\.00\s*0\.50\s*0\.00/);
+});
+
+test("edits can be undone and redone", async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  const undo = page.getByRole("button", { name: "Undo" });
+  const redo = page.getByRole("button", { name: "Redo" });
+  await expect(undo).toBeDisabled();
+  await expect(redo).toBeDisabled();
+
+  await page.locator("canvas").click();
+  const x = page.locator(".inspector fieldset").filter({ hasText: "Transform" }).getByLabel("translation.x");
+  await x.fill("2");
+  await x.press("Enter");
+
+  const position = page.locator(".transform-panel").getByRole("row", { name: /Position/ });
+  await expect(position).toHaveText(/2\.00\s*0\.50\s*0\.00/);
+  await expect(undo).toBeEnabled();
+
+  await undo.click();
+  await expect(position).toHaveText(/0\.00\s*0\.50\s*0\.00/);
+  await expect(undo).toBeDisabled();
+  await expect(redo).toBeEnabled();
+
+  // The shortcut works once the canvas has focus again.
+  await page.locator("canvas").focus();
+  await page.keyboard.press("Control+Shift+KeyZ");
+  await expect(position).toHaveText(/2\.00\s*0\.50\s*0\.00/);
+  await expect(redo).toBeDisabled();
+});
+
+test("primitives can be added and the selected one deleted", async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  const toolbar = page.locator(".toolbar");
+  const tree = page.locator(".inspector .entity-tree");
+  await expect(toolbar.getByRole("button", { name: "Delete" })).toBeDisabled();
+
+  await toolbar.getByRole("button", { name: "Sphere" }).click();
+  await expect(tree.getByRole("button", { name: "Sphere 1" })).toBeVisible();
+  await expect(page.getByText(/^Selected: /)).toBeVisible();
+
+  await toolbar.getByRole("button", { name: "Torus" }).click();
+  await expect(tree.getByRole("button", { name: "Torus 2" })).toBeVisible();
+
+  await toolbar.getByRole("button", { name: "Delete" }).click();
+  await expect(tree.getByRole("button", { name: "Torus 2" })).toHaveCount(0);
+  await expect(tree.getByRole("button", { name: "Sphere 1" })).toBeVisible();
+  await expect(page.getByText("Nothing selected")).toBeVisible();
+});
+
+test("a page runs two scenes side by side", async ({ page }) => {
+  const errors: Error[] = [];
+  page.on("pageerror", (error) => errors.push(error));
+
+  await page.goto("http://localhost:3000/viewports");
+  await expect(page.locator("canvas")).toHaveCount(3);
+
+  // Each app sizes its canvas to its container once it renders.
+  for (const id of ["#main_viewport", "#second_scene"]) {
+    const width = () =>
+      page.locator(id).evaluate((canvas) => (canvas as HTMLCanvasElement).width);
+    await expect.poll(width, { timeout: 20000 }).toBeGreaterThan(300);
+  }
+
+  // The second app draws its frames into the canvas, over the transparent blank one.
+  const alpha = () =>
+    page.locator("#second_scene").evaluate((canvas) => {
+      const context = (canvas as HTMLCanvasElement).getContext("2d")!;
+      const { width, height } = canvas as HTMLCanvasElement;
+      return context.getImageData(width / 2, height / 2, 1, 1).data[3];
+    });
+  await expect.poll(alpha, { timeout: 20000 }).toBe(255);
+  expect(errors).toEqual([]);
+});
+
+test("navigating away releases the Bevy app", async ({ page }) => {
+  test.setTimeout(120 * 1000);
+
+  // Keep track of every WebGL context the page creates.
+  await page.addInitScript(() => {
+    const contexts: WebGL2RenderingContext[] = [];
+    const getContext = HTMLCanvasElement.prototype.getContext;
+
+    HTMLCanvasElement.prototype.getContext = function (
+      this: HTMLCanvasElement,
+      type: string,
+      ...args: unknown[]
+    ) {
+      const context = getContext.call(this, type, ...args);
+      if (type === "webgl2" && context && !contexts.includes(context)) {
+        contexts.push(context);
+      }
+      return context;
+    } as typeof getContext;
+
+    (window as any).liveWebGlContexts = () =>
+      contexts.filter((context) => !context.isContextLost()).length;
+  });
+
+  const liveWebGlContexts = () =>
+    page.evaluate(() => (window as any).liveWebGlContexts() as number);
+
+  await page.goto("http://localhost:3000/");
+
+  for (let i = 0; i < 10; i++) {
+    await page.getByRole("link", { name: "Go to Bevy Canvas" }).click();
+    await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+    await expect(page.locator("canvas")).toHaveCount(1);
+    expect(await liveWebGlContexts()).toBe(1);
+
+    await page.goBack();
+    await expect(page.locator("h1")).toHaveText("Welcome to Leptos!");
+    await expect(page.locator("canvas")).toHaveCount(0);
+    await expect.poll(liveWebGlContexts).toBe(0);
+  }
+});
+
+test("saving a scene lists it and it can be loaded and deleted", async ({
+  page,
+}) => {
+  const title = `Saved scene ${Date.now()}`;
+
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+
+  await page.getByPlaceholder("Scene title").fill(title);
+  await page.getByRole("button", { name: "Save scene" }).click();
+
+  const saved = page.locator(".scene-list li").filter({ hasText: title });
+  await expect(saved).toBeVisible();
+
+  await saved.getByRole("button", { name: "Load" }).click();
+  await expect(page.locator(".scene-library .error")).toHaveCount(0);
+
+  await saved.getByRole("button", { name: "Delete" }).click();
+  await expect(saved).toHaveCount(0);
+});
@@ -0,0 +1,41 @@
+import { test, expect } from "@playwright/test";
+
+test("edits are shared with everyone in the room", async ({ browser }) => {
+  const room = `room-${Date.now()}`;
+  const context = await browser.newContext();
+  const alice = await context.newPage();
+  const bob = await context.newPage();
+
+  for (const page of [alice, bob]) {
+    await page.goto(`http://localhost:3000/canvas?room=${room}`);
+    await expect(page.getByText(`Editing together in room ${room}`)).toBeVisible();
+    await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+  }
+
+  await alice.locator('input[type="color"]').fill("#ff0000");
+  await expect(bob.locator('input[type="color"]')).toHaveValue("#ff0000");
+
+  await bob.locator('input[type="color"]').fill("#00ff00");
+  await expect(alice.locator('input[type="color"]')).toHaveValue("#00ff00");
+
+  await context.close();
+});
+
+test("rooms don't share edits with each other", async ({ browser }) => {
+  const context = await browser.newContext();
+  const alice = await context.newPage();
+  const bob = await context.newPage();
+
+  await alice.goto(`http://localhost:3000/canvas?room=a-${Date.now()}`);
+  await bob.goto(`http://localhost:3000/canvas?room=b-${Date.now()}`);
+  for (const page of [alice, bob]) {
+    await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+  }
+
+  const before = await bob.locator('input[type="color"]').inputValue();
+  await alice.locator('input[type="color"]').fill("#ff0000");
+  await alice.waitForTimeout(1000);
+  await expect(bob.locator('input[type="color"]')).toHaveValue(before);
+
+  await context.close();
+});
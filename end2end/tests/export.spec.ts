@@ -0,0 +1,98 @@
+import { test, expect, Page } from "@playwright/test";
+import { readFileSync } from "fs";
+
+/// The JSON chunk of a .glb file.
+function glbJson(glb: Buffer) {
+  expect(glb.toString("latin1", 0, 4)).toBe("glTF");
+  const length = glb.readUInt32LE(12);
+  return JSON.parse(glb.toString("utf8", 20, 20 + length));
+}
+
+async function exportScene(page: Page) {
+  const download = page.waitForEvent("download");
+  await page.locator(".toolbar").getByRole("button", { name: "Export .glb" }).click();
+  const file = await download;
+  expect(file.suggestedFilename()).toBe("scene.glb");
+  return readFileSync((await file.path())!);
+}
+
+async function dropGlb(page: Page, name: string, glb: Buffer) {
+  await page.evaluate(
+    ([name, base64]) => {
+      const bytes = Uint8Array.from(atob(base64), (char) => char.charCodeAt(0));
+      const transfer = new DataTransfer();
+      transfer.items.add(new File([bytes], name));
+      document
+        .querySelector(".editor")!
+        .dispatchEvent(
+          new DragEvent("drop", { dataTransfer: transfer, bubbles: true, cancelable: true }),
+        );
+    },
+    [name, glb.toString("base64")],
+  );
+}
+
+test.beforeEach(async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+});
+
+test("the default scene is exported as binary glTF", async ({ page }) => {
+  const gltf = glbJson(await exportScene(page));
+
+  expect(gltf.scenes[gltf.scene].name).toBe("Default cube");
+  const cube = gltf.nodes.find((node: any) => node.name === "Cube");
+  expect(cube.translation).toEqual([0, 0.5, 0]);
+
+  // Factors are linear; the cube is sRGB (0.3, 0.6, 0.9).
+  const material = gltf.materials[gltf.meshes[cube.mesh].primitives[0].material];
+  const [red, green, blue, alpha] = material.pbrMetallicRoughness.baseColorFactor;
+  expect(red).toBeCloseTo(0.0732, 3);
+  expect(green).toBeCloseTo(0.3185, 3);
+  expect(blue).toBeCloseTo(0.7874, 3);
+  expect(alpha).toBe(1);
+
+  expect(gltf.extensionsUsed).toContain("KHR_lights_punctual");
+  expect(gltf.extensions.KHR_lights_punctual.lights).toEqual([
+    expect.objectContaining({ type: "point", range: 20 }),
+  ]);
+  expect(gltf.nodes.filter((node: any) => node.camera !== undefined)).toHaveLength(1);
+});
+
+test("an exported scene imports with the same transforms and colors", async ({ page }) => {
+  const tree = page.locator(".inspector .entity-tree");
+  const transform = page.locator(".inspector fieldset").filter({ hasText: "Transform" });
+  const material = page.locator(".inspector fieldset").filter({ hasText: "MeshMaterial3d" });
+
+  // Edit the cube and add a sphere, so the export holds more than the described scene.
+  await page.locator('input[type="color"]').first().fill("#ff8000");
+  await tree.getByRole("button", { name: "Cube", exact: true }).click();
+  await transform.getByLabel("translation.x").fill("2");
+  await transform.getByLabel("translation.x").press("Enter");
+  await expect(material.getByLabel("base_color")).toHaveValue("#ff8000");
+  await page.locator(".toolbar").getByRole("button", { name: "Sphere" }).click();
+  await expect(tree.getByRole("button", { name: "Sphere 1" })).toBeVisible();
+
+  const glb = await exportScene(page);
+  const gltf = glbJson(glb);
+  expect(gltf.nodes.map((node: any) => node.name)).toEqual(
+    expect.arrayContaining(["Cube", "Sphere 1"]),
+  );
+
+  await dropGlb(page, "scene.glb", glb);
+  await expect(tree.getByRole("button", { name: "scene.glb" })).toBeVisible();
+  await expect(page.locator(".error")).toHaveCount(0);
+
+  // The imported cube is a child of the model; its mesh is a child of the cube node.
+  const cubes = tree.getByRole("button", { name: "Cube", exact: true });
+  await expect(cubes).toHaveCount(2);
+  await cubes.nth(1).click();
+  await expect(transform.getByLabel("translation.x")).toHaveValue("2");
+  await expect(transform.getByLabel("translation.y")).toHaveValue("0.5");
+  await expect(transform.getByLabel("translation.z")).toHaveValue("0");
+
+  await tree.getByRole("button", { name: "Mesh", exact: true }).first().click();
+  await expect(material.getByLabel("base_color")).toHaveValue("#ff8000");
+  await expect(material.getByLabel("metallic")).toHaveValue("0");
+  await expect(material.getByLabel("perceptual_roughness")).toHaveValue("0.5");
+});
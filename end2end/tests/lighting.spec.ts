@@ -0,0 +1,43 @@
+import { test, expect } from "@playwright/test";
+
+test.beforeEach(async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+});
+
+test("lights are added, edited and removed", async ({ page }) => {
+  const panel = page.locator(".lighting-panel");
+  const point = panel.locator("fieldset").filter({ hasText: "Point light 1" });
+  await expect(point.getByLabel("Intensity (lm)")).toHaveValue("1500");
+  await expect(point.getByLabel("Shadows")).toBeChecked();
+
+  await panel.getByRole("button", { name: "Add spot light" }).click();
+  const spot = panel.locator("fieldset").filter({ hasText: "Spot light 1" });
+  await expect(spot.getByLabel("Outer angle (°)")).toHaveValue("30");
+  await expect(spot.getByLabel("Illuminance (lx)")).toHaveCount(0);
+
+  await spot.getByLabel("Range (m)").fill("8");
+  await spot.getByLabel("Range (m)").press("Enter");
+  await expect(spot.getByLabel("Range (m)")).toHaveValue("8");
+  await page.getByRole("button", { name: "Undo" }).click();
+  await expect(spot.getByLabel("Range (m)")).toHaveValue("20");
+
+  await point.getByRole("button", { name: "Remove" }).click();
+  await expect(point).toHaveCount(0);
+  await expect(spot).toBeVisible();
+});
+
+test("the environment switches ambient light and the environment map", async ({ page }) => {
+  const panel = page.locator(".lighting-panel");
+  await expect(panel.getByLabel("Brightness (cd/m²)")).toHaveValue("80");
+  await expect(panel.getByLabel("Horizon")).toHaveCount(0);
+
+  await panel.getByLabel("Ambient light").uncheck();
+  await expect(panel.getByLabel("Brightness (cd/m²)")).toHaveCount(0);
+
+  await panel.getByLabel("Environment map").check();
+  await expect(panel.getByLabel("Intensity (cd/m²)")).toHaveValue("500");
+
+  await panel.getByLabel("Shadow quality").selectOption("High");
+  await expect(panel.getByLabel("Shadow quality")).toHaveValue("High");
+});
@@ -0,0 +1,65 @@
+import { test, expect } from "@playwright/test";
+
+test.beforeEach(async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+});
+
+test("the material panel edits the selected object", async ({ page }) => {
+  const panel = page.locator(".material-panel");
+  const material = page.locator(".inspector fieldset").filter({ hasText: "MeshMaterial3d" });
+  await expect(panel).toContainText("Select an object to edit its material.");
+
+  await page.locator("canvas").click();
+  await expect(panel.getByLabel("Base color", { exact: true })).toHaveValue("#4d99e6");
+  await expect(panel.getByLabel("Roughness", { exact: true })).toHaveValue("0.5");
+
+  await panel.getByLabel("Metallic", { exact: true }).fill("0.8");
+  await expect(material.getByLabel("metallic")).toHaveValue("0.8");
+  await panel.getByLabel("Reflectance", { exact: true }).fill("0.2");
+  await expect(material.getByLabel("reflectance")).toHaveValue("0.2");
+
+  await expect(panel.getByLabel("Alpha cutoff")).toHaveCount(0);
+  await panel.getByLabel("Alpha mode").selectOption("Mask");
+  await expect(panel.getByLabel("Alpha cutoff")).toHaveValue("0.5");
+
+  const texture = panel.getByLabel("Base color map");
+  await texture.fill("textures/checker.png");
+  await texture.press("Enter");
+  await expect(texture).toHaveValue("textures/checker.png");
+
+  await page.getByRole("button", { name: "Undo" }).click();
+  await expect(texture).toHaveValue("");
+});
+
+test("objects with the same material name share the material", async ({ page }) => {
+  const panel = page.locator(".material-panel");
+  const name = panel.getByLabel("Name");
+  const tree = page.locator(".inspector .entity-tree");
+  const material = page.locator(".inspector fieldset").filter({ hasText: "MeshMaterial3d" });
+
+  await page.locator("canvas").click();
+  await name.fill("Glossy");
+  await name.press("Enter");
+  await expect(name).toHaveValue("Glossy");
+
+  // New primitives are selected right away.
+  await page.locator(".toolbar").getByRole("button", { name: "Sphere" }).click();
+  await expect(tree.getByRole("button", { name: "Sphere 1" })).toBeVisible();
+  await expect(name).toHaveValue("");
+  await name.fill("Glossy");
+  await name.press("Enter");
+  await expect(panel).toContainText("Shared by 2 objects");
+  await expect(panel.getByLabel("Base color", { exact: true })).toHaveValue("#4d99e6");
+
+  await panel.getByLabel("Roughness", { exact: true }).fill("0.1");
+  await tree.getByRole("button", { name: "Cube", exact: true }).click();
+  await expect(material.getByLabel("perceptual_roughness")).toHaveValue("0.1");
+
+  // Without a name the sphere gets a material of its own again.
+  await name.fill("");
+  await name.press("Enter");
+  await expect(panel).not.toContainText("Shared by");
+  await panel.getByLabel("Roughness", { exact: true }).fill("0.9");
+  await expect(material.getByLabel("perceptual_roughness")).toHaveValue("0.1");
+});
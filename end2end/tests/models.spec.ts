@@ -0,0 +1,72 @@
+import { test, expect, Page } from "@playwright/test";
+
+// A single triangle, with its buffer embedded as a data URI.
+const TRIANGLE_GLTF = JSON.stringify({
+  asset: { version: "2.0" },
+  scene: 0,
+  scenes: [{ nodes: [0] }],
+  nodes: [{ mesh: 0 }],
+  meshes: [{ primitives: [{ attributes: { POSITION: 0 } }] }],
+  buffers: [
+    {
+      byteLength: 36,
+      uri: "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA",
+    },
+  ],
+  bufferViews: [{ buffer: 0, byteLength: 36 }],
+  accessors: [
+    {
+      bufferView: 0,
+      componentType: 5126,
+      count: 3,
+      type: "VEC3",
+      min: [0, 0, 0],
+      max: [1, 1, 0],
+    },
+  ],
+});
+
+async function dropFile(page: Page, name: string, contents: string) {
+  await page.evaluate(
+    ([name, contents]) => {
+      const transfer = new DataTransfer();
+      transfer.items.add(new File([contents], name));
+      document
+        .querySelector(".editor")!
+        .dispatchEvent(
+          new DragEvent("drop", { dataTransfer: transfer, bubbles: true, cancelable: true }),
+        );
+    },
+    [name, contents],
+  );
+}
+
+test.beforeEach(async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+});
+
+test("a dropped glTF model is added to the scene", async ({ page }) => {
+  await dropFile(page, "triangle.gltf", TRIANGLE_GLTF);
+
+  await expect(
+    page.locator(".inspector .entity-tree").getByRole("button", { name: "triangle.gltf" }),
+  ).toBeVisible();
+  await expect(page.locator(".error")).toHaveCount(0);
+});
+
+test("files that aren't glTF are rejected", async ({ page }) => {
+  await dropFile(page, "notes.txt", "hello");
+  await expect(page.locator(".error")).toContainText("notes.txt is not a .glb or .gltf file");
+
+  await dropFile(page, "broken.glb", "definitely not binary glTF");
+  await expect(page.locator(".error")).toContainText("the model is not valid glTF");
+});
+
+test("models that refer to other files are rejected", async ({ page }) => {
+  const gltf = JSON.parse(TRIANGLE_GLTF);
+  gltf.buffers[0].uri = "triangle.bin";
+
+  await dropFile(page, "triangle.gltf", JSON.stringify(gltf));
+  await expect(page.locator(".error")).toContainText("refers to triangle.bin");
+});
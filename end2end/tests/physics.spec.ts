@@ -0,0 +1,39 @@
+import { test, expect } from "@playwright/test";
+
+test.beforeEach(async ({ page }) => {
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+});
+
+test("dynamic bodies fall onto the ground", async ({ page }) => {
+  const controls = page.locator(".physics-controls");
+  const body = controls.getByLabel("Body");
+  const position = page.locator(".transform-panel").getByRole("row", { name: /Position/ });
+  await expect(body).toBeDisabled();
+
+  await page.locator("canvas").click();
+  const y = page.locator(".inspector fieldset").filter({ hasText: "Transform" }).getByLabel("translation.y");
+  await y.fill("3");
+  await y.press("Enter");
+  await expect(body).toHaveValue("none");
+  await body.selectOption("Dynamic");
+
+  // Steps are the same every time: 3 m minus 9.81 m/s² over 55 (1/60 s)².
+  for (let i = 0; i < 10; i++) {
+    await controls.getByRole("button", { name: "Step" }).click();
+  }
+  await expect(controls).toContainText("Step 10 (0.17 s)");
+  await expect(position).toHaveText(/0\.00\s*2\.85\s*0\.00/);
+
+  await controls.getByRole("button", { name: "Reset" }).click();
+  await expect(position).toHaveText(/0\.00\s*3\.00\s*0\.00/);
+  await expect(controls.getByRole("button", { name: "Reset" })).toBeDisabled();
+
+  await controls.getByRole("button", { name: "Play" }).click();
+  await expect(controls.getByRole("button", { name: "Step" })).toBeDisabled();
+  await expect(position).toHaveText(/0\.00\s*0\.50\s*0\.00/, { timeout: 5000 });
+  await controls.getByRole("button", { name: "Pause" }).click();
+
+  await page.getByRole("button", { name: "Undo" }).click();
+  await expect(body).toHaveValue("none");
+});
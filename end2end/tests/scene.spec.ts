@@ -0,0 +1,48 @@
+import { test, expect } from "@playwright/test";
+
+test("unknown scene ids show a 404", async ({ page }) => {
+  const response = await page.goto("http://localhost:3000/scene/no-such-scene");
+
+  expect(response?.status()).toBe(404);
+  await expect(page).toHaveTitle("Page not found");
+  await expect(page.locator("h2")).toHaveText("Page not found.");
+});
+
+test("stored scenes render with their title", async ({ page }) => {
+  const title = `Linked scene ${Date.now()}`;
+
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+  await page.getByPlaceholder("Scene title").fill(title);
+  await page.getByRole("button", { name: "Save scene" }).click();
+
+  const link = page.getByRole("link", { name: title });
+  const href = await link.getAttribute("href");
+  expect(href).toMatch(/^\/scene\/[0-9a-f]+$/);
+
+  const response = await page.goto(`http://localhost:3000${href}`);
+
+  expect(response?.status()).toBe(200);
+  await expect(page).toHaveTitle(title);
+  await expect(page.locator("h2")).toHaveText(title);
+  await expect(page.locator("canvas#stored_scene")).toBeVisible();
+});
+
+test("stored scenes have a thumbnail", async ({ page, request }) => {
+  const title = `Thumbnail scene ${Date.now()}`;
+
+  await page.goto("http://localhost:3000/canvas");
+  await expect(page.getByText(/\d+ FPS/)).toBeVisible({ timeout: 20000 });
+  await page.getByPlaceholder("Scene title").fill(title);
+  await page.getByRole("button", { name: "Save scene" }).click();
+
+  const href = await page.getByRole("link", { name: title }).getAttribute("href");
+  const id = href?.replace("/scene/", "");
+
+  const thumbnail = await request.get(`http://localhost:3000/thumb/${id}.png`);
+  expect(thumbnail.status()).toBe(200);
+  expect(thumbnail.headers()["content-type"]).toBe("image/png");
+
+  const missing = await request.get("http://localhost:3000/thumb/no-such-scene.png");
+  expect(missing.status()).toBe(404);
+});
@@ -1,14 +1,49 @@
 use leptos::prelude::*;
-use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
+use leptos_meta::{provide_meta_context, Meta, MetaTags, Stylesheet, Title};
 use leptos_router::{
     components::{Route, Router, Routes},
-    StaticSegment,
+    hooks::use_params_map,
+    ParamSegment, StaticSegment,
 };
 
 use bevy::prelude::*;
 use leptos_bevy_canvas::prelude::*;
 use std::rc::Rc;
 
+use crate::history::{EditCommand, SceneEdit};
+use crate::scene_description::{DescribedScene, SceneDescription};
+use crate::scene_storage::load_scene;
+use crate::scene_text::SceneText;
+use crate::scene_view::{CanvasFit, SceneView, SceneViewport};
+#[cfg(target_arch = "wasm32")]
+use crate::{
+    collaboration::CollaborationPlugin,
+    cube_color::CubeColor,
+    history::{HistoryCommand, HistoryState},
+    inspector::{FieldValue, InspectorEdit, InspectorPlugin, InspectorSnapshot},
+    keyboard_focus::{canvas_focused, move_cube_with_keys},
+    lighting::{LightKind, LightingCommand, LightingState},
+    material_editor::{MaterialEdit, MaterialEditorPlugin, SelectedMaterial, BUNDLED_TEXTURES},
+    model_import::{ImportModel, ModelImportPlugin},
+    model_storage::{upload_model, ModelError, ModelFormat, MAX_MODEL_SIZE},
+    orbit_camera::CameraCommand,
+    physics::{PhysicsCommand, PhysicsPlugin, PhysicsState, TIME_STEP},
+    primitives::{ObjectCommand, Primitive, PrimitivesPlugin},
+    scene_description::{
+        AlphaModeDescription, AmbientDescription, EnvironmentDescription,
+        EnvironmentMapDescription, LightDescription, MaterialDescription, RigidBody, SceneCommand,
+        SceneSnapshot, ShadowQuality, TextureSlot, TransformDescription,
+    },
+    scene_events::SceneEvent,
+    scene_export::{ExportScene, ExportedScene, SceneExportPlugin},
+    scene_storage::{list_scenes, DeleteScene, SaveScene},
+    transform_gizmo::{GizmoMode, GizmoSettings, SelectedTransform, TransformGizmoPlugin},
+};
+#[cfg(target_arch = "wasm32")]
+use leptos_router::hooks::use_query_map;
+#[cfg(target_arch = "wasm32")]
+use web_sys::{DragEvent, FormData};
+
 /// -------- Leptos Shell --------
 pub fn shell(options: LeptosOptions) -> impl IntoView {
     view! {
@@ -38,9 +73,11 @@ pub fn App() -> impl IntoView {
 
         <Router>
             <main>
-                <Routes fallback=|| "Page not found.".into_view()>
+                <Routes fallback=NotFound>
                     <Route path=StaticSegment("") view=HomePage />
-                    <Route path=StaticSegment("canvas") view=CanvasPage />
+                    <Route path=StaticSegment("canvas") view=|| view! { <CanvasPage /> } />
+                    <Route path=StaticSegment("viewports") view=ViewportsPage />
+                    <Route path=(StaticSegment("scene"), ParamSegment("id")) view=ScenePage />
                 </Routes>
             </main>
         </Router>
@@ -59,6 +96,26 @@ fn HomePage() -> impl IntoView {
         <p>
             <a href="/canvas">"Go to Bevy Canvas"</a>
         </p>
+        <p>
+            <a href="/viewports">"Go to Scene Viewports"</a>
+        </p>
+    }
+}
+
+/// -------- Leptos Not Found --------
+#[component]
+fn NotFound() -> impl IntoView {
+    #[cfg(feature = "ssr")]
+    if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+        response.set_status(axum::http::StatusCode::NOT_FOUND);
+    }
+
+    view! {
+        <Title text="Page not found" />
+        <h2>"Page not found."</h2>
+        <p>
+            <a href="/">"Back to the start"</a>
+        </p>
     }
 }
 
@@ -73,11 +130,51 @@ pub struct TextEvent {
 //
 #[cfg(target_arch = "wasm32")]
 #[component]
-fn CanvasPage() -> impl IntoView {
-    // 1. Bridge between Leptos and Bevy
+fn CanvasPage(
+    /// Sizing of the canvas. Defaults to a 16:9 aspect ratio.
+    #[prop(default = CanvasFit::AspectRatio(16.0 / 9.0))]
+    fit: CanvasFit,
+) -> impl IntoView {
+    // 1. Bridges between Leptos and Bevy
     let (text_event_sender, bevy_text_receiver) = event_l2b::<TextEvent>();
+    let (scene_event_receiver, bevy_scene_sender) = event_b2l::<SceneEvent>();
+    let (cube_color, bevy_cube_color) = signal_synced(CubeColor::default());
+    let (camera_command_sender, bevy_camera_receiver) = event_l2b::<CameraCommand>();
+    let (scene_command_sender, bevy_scene_command_receiver) = event_l2b::<SceneCommand>();
+    let (snapshot_receiver, bevy_snapshot_sender) = event_b2l::<SceneSnapshot>();
+    let (gizmo_settings, bevy_gizmo_settings) = signal_synced(GizmoSettings::default());
+    let (selected_transform, bevy_selected_transform) = event_b2l::<SelectedTransform>();
+    let (inspector_receiver, bevy_inspector_sender) = event_b2l::<InspectorSnapshot>();
+    let (inspector_edit_sender, bevy_inspector_receiver) = event_l2b::<InspectorEdit>();
+    let (history_command_sender, bevy_history_receiver) = event_l2b::<HistoryCommand>();
+    let (history_state, bevy_history_sender) = event_b2l::<HistoryState>();
+    let (object_command_sender, bevy_object_receiver) = event_l2b::<ObjectCommand>();
+    let (import_sender, bevy_import_receiver) = event_l2b::<ImportModel>();
+    let (export_sender, bevy_export_receiver) = event_l2b::<ExportScene>();
+    let (exported_receiver, bevy_exported_sender) = event_b2l::<ExportedScene>();
+    let (material_edit_sender, bevy_material_receiver) = event_l2b::<MaterialEdit>();
+    let (selected_material, bevy_selected_material) = event_b2l::<SelectedMaterial>();
+    let (lighting_command_sender, bevy_lighting_receiver) = event_l2b::<LightingCommand>();
+    let (lighting_state, bevy_lighting_sender) = event_b2l::<LightingState>();
+    let (physics_command_sender, bevy_physics_receiver) = event_l2b::<PhysicsCommand>();
+    let (physics_state, bevy_physics_sender) = event_b2l::<PhysicsState>();
+
+    // Everyone opening the page with the same `?room=` edits the same scene.
+    let room = use_query_map().read_untracked().get("room");
 
-    // 2. Input handler
+    // 2. Mirror scene events into signals
+    let clicks = RwSignal::new(0);
+    let fps = RwSignal::new(None::<f64>);
+    let selected = RwSignal::new(None::<Entity>);
+
+    Effect::new(move || match scene_event_receiver.get() {
+        Some(SceneEvent::Clicked { .. }) => *clicks.write() += 1,
+        Some(SceneEvent::SelectionChanged { entity }) => selected.set(entity),
+        Some(SceneEvent::FrameStats { fps: value }) => fps.set(Some(value)),
+        None => {}
+    });
+
+    // 3. Input handlers
     let on_input = move |evt| {
         text_event_sender
             .send(TextEvent {
@@ -86,11 +183,1318 @@ fn CanvasPage() -> impl IntoView {
             .ok();
     };
 
-    // 3. Render input + Bevy canvas (client only)
+    let on_color = move |evt| {
+        if let Some(color) = CubeColor::from_hex(&event_target_value(&evt)) {
+            cube_color.set(color);
+        }
+    };
+
+    let send_camera_command = move |command| {
+        camera_command_sender.send(command).ok();
+    };
+
+    let history = move || history_state.get().unwrap_or_default();
+    let send_history_command = move |command| {
+        history_command_sender.send(command).ok();
+    };
+
+    let send_object_command = move |command| {
+        object_command_sender.send(command).ok();
+    };
+
+    // Models dropped onto the editor are uploaded first and then loaded from the server.
+    let upload = Action::new_local(|data: &FormData| upload_model(data.clone().into()));
+    let upload_error = RwSignal::new(None::<String>);
+
+    Effect::new(move || match upload.value().get() {
+        Some(Ok(model)) => {
+            import_sender.send(ImportModel(model)).ok();
+        }
+        Some(Err(err)) => upload_error.set(Some(format!("Uploading the model failed: {err}"))),
+        None => {}
+    });
+
+    let on_drop = move |evt: DragEvent| {
+        evt.prevent_default();
+        let Some(file) = evt
+            .data_transfer()
+            .and_then(|transfer| transfer.files())
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+
+        let checked = ModelFormat::from_file_name(&file.name()).and_then(|_| {
+            if file.size() as u64 > MAX_MODEL_SIZE {
+                Err(ModelError::TooLarge)
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(err) = checked {
+            upload_error.set(Some(format!("Uploading the model failed: {err}")));
+            return;
+        }
+
+        let Ok(data) = FormData::new() else {
+            return;
+        };
+        if data
+            .append_with_blob_and_filename("model", &file, &file.name())
+            .is_ok()
+        {
+            upload_error.set(None);
+            upload.dispatch_local(data);
+        }
+    };
+
+    Effect::new(move || {
+        if let Some(ExportedScene(glb)) = exported_receiver.get() {
+            download("scene.glb", "model/gltf-binary", &glb);
+        }
+    });
+
+    // 4. Render inputs + Bevy canvas (client only)
     view! {
         <h2>"Bevy Canvas Integration"</h2>
         <input type="text" on:input=on_input />
-        <BevyCanvas init=move || init_bevy_app(bevy_text_receiver.clone()) />
+        <input type="color" prop:value=move || cube_color.get().to_hex() on:input=on_color />
+        <button on:click=move |_| send_camera_command(CameraCommand::Reset)>"Reset camera"</button>
+        <button on:click=move |_| {
+            send_camera_command(CameraCommand::FrameSelected)
+        }>"Frame selected"</button>
+        <p>"Cube clicks: " {clicks}</p>
+        <p>
+            {move || match selected.get() {
+                Some(entity) => format!("Selected: {entity}"),
+                None => "Nothing selected".to_string(),
+            }}
+        </p>
+        <p>{move || fps.get().map(|fps| format!("{fps:.0} FPS"))}</p>
+        {room.clone().map(|room| view! { <p>"Editing together in room " <strong>{room}</strong></p> })}
+        <div class="toolbar">
+            <button
+                title="Ctrl+Z"
+                disabled=move || !history().can_undo
+                on:click=move |_| send_history_command(HistoryCommand::Undo)
+            >
+                "Undo"
+            </button>
+            <button
+                title="Ctrl+Shift+Z"
+                disabled=move || !history().can_redo
+                on:click=move |_| send_history_command(HistoryCommand::Redo)
+            >
+                "Redo"
+            </button>
+            {Primitive::ALL
+                .map(|primitive| {
+                    view! {
+                        <button on:click=move |_| {
+                            send_object_command(ObjectCommand::Spawn(primitive))
+                        }>{primitive.label()}</button>
+                    }
+                })
+                .collect_view()}
+            <button
+                title="Delete"
+                disabled=move || selected.get().is_none()
+                on:click=move |_| send_object_command(ObjectCommand::DeleteSelected)
+            >
+                "Delete"
+            </button>
+            <button on:click=move |_| {
+                export_sender.send(ExportScene).ok();
+            }>"Export .glb"</button>
+        </div>
+        <PhysicsControls state=physics_state commands=physics_command_sender />
+        <p class="hint">"Drop a .glb or .gltf file onto the canvas to add it to the scene."</p>
+        {move || upload.pending().get().then(|| view! { <p>"Uploading model…"</p> })}
+        {move || upload_error.get().map(|error| view! { <p class="error">{error}</p> })}
+        <div
+            class="editor"
+            on:dragover=|evt: DragEvent| evt.prevent_default()
+            on:drop=on_drop
+        >
+            <SceneView
+                fit=fit
+                plugins=move |app: &mut App| {
+                    init_bevy_app(
+                        app,
+                        bevy_text_receiver,
+                        bevy_scene_sender,
+                        bevy_cube_color,
+                        bevy_camera_receiver,
+                        bevy_scene_command_receiver,
+                        bevy_snapshot_sender,
+                    );
+                    app.add_plugins((
+                        TransformGizmoPlugin,
+                        InspectorPlugin,
+                        PrimitivesPlugin,
+                        ModelImportPlugin,
+                        SceneExportPlugin,
+                        MaterialEditorPlugin,
+                        PhysicsPlugin,
+                    ))
+                        .sync_leptos_signal_with_resource(bevy_gizmo_settings)
+                        .export_event_to_leptos(bevy_selected_transform)
+                        .export_event_to_leptos(bevy_inspector_sender)
+                        .import_event_from_leptos(bevy_inspector_receiver)
+                        .import_event_from_leptos(bevy_history_receiver)
+                        .export_event_to_leptos(bevy_history_sender)
+                        .import_event_from_leptos(bevy_object_receiver)
+                        .import_event_from_leptos(bevy_import_receiver)
+                        .import_event_from_leptos(bevy_export_receiver)
+                        .export_event_to_leptos(bevy_exported_sender)
+                        .import_event_from_leptos(bevy_material_receiver)
+                        .export_event_to_leptos(bevy_selected_material)
+                        .import_event_from_leptos(bevy_lighting_receiver)
+                        .export_event_to_leptos(bevy_lighting_sender)
+                        .import_event_from_leptos(bevy_physics_receiver)
+                        .export_event_to_leptos(bevy_physics_sender);
+                    if let Some(room) = room {
+                        app.add_plugins(CollaborationPlugin { room });
+                    }
+                }
+            />
+            <TransformPanel settings=gizmo_settings transform=selected_transform />
+            <MaterialPanel selected=selected_material edits=material_edit_sender />
+            <LightingPanel state=lighting_state commands=lighting_command_sender />
+            <InspectorPanel
+                snapshot=inspector_receiver
+                edits=inspector_edit_sender
+                selected=selected
+            />
+        </div>
+        <SceneLibrary commands=scene_command_sender snapshots=snapshot_receiver />
+    }
+}
+
+/// Hands `bytes` to the browser as a file download.
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, mime_type: &str, bytes: &[u8]) {
+    use std::time::Duration;
+    use web_sys::js_sys::{Array, Uint8Array};
+    use web_sys::wasm_bindgen::JsCast;
+    use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let url = Blob::new_with_u8_array_sequence_and_options(
+        &Array::of1(&Uint8Array::from(bytes)),
+        &options,
+    )
+    .and_then(|blob| Url::create_object_url_with_blob(&blob));
+    let anchor = document()
+        .create_element("a")
+        .ok()
+        .and_then(|anchor| anchor.dyn_into::<HtmlAnchorElement>().ok());
+
+    if let (Ok(url), Some(anchor)) = (url, anchor) {
+        anchor.set_href(&url);
+        anchor.set_download(file_name);
+        anchor.click();
+        // The download starts asynchronously, so the URL has to outlive this call for a bit.
+        set_timeout(
+            move || {
+                Url::revoke_object_url(&url).ok();
+            },
+            Duration::from_secs(10),
+        );
+    }
+}
+
+/// Gizmo mode and snapping, and the exact transform of the selected entity.
+#[cfg(target_arch = "wasm32")]
+#[component]
+fn TransformPanel(
+    settings: RwSignalSynced<GizmoSettings>,
+    transform: LeptosEventReceiver<SelectedTransform>,
+) -> impl IntoView {
+    let mode_button = move |mode: GizmoMode, label: &'static str| {
+        view! {
+            <button
+                class:active=move || settings.get().mode == mode
+                on:click=move |_| settings.write().mode = mode
+            >
+                {label}
+            </button>
+        }
+    };
+
+    let values = move || match transform
+        .get()
+        .and_then(|SelectedTransform(transform)| transform)
+    {
+        Some(transform) => {
+            let TransformDescription {
+                translation,
+                rotation,
+                scale,
+            } = TransformDescription::from(&transform);
+            let row = |label: &'static str, [x, y, z]: [f32; 3]| {
+                view! {
+                    <tr>
+                        <th>{label}</th>
+                        <td>{format!("{x:.2}")}</td>
+                        <td>{format!("{y:.2}")}</td>
+                        <td>{format!("{z:.2}")}</td>
+                    </tr>
+                }
+            };
+
+            view! {
+                <table>
+                    {row("Position", translation)}
+                    {row("Rotation", rotation)}
+                    {row("Scale", scale)}
+                </table>
+            }
+            .into_any()
+        }
+        None => view! { <p>"Select an object to transform it."</p> }.into_any(),
+    };
+
+    view! {
+        <aside class="transform-panel">
+            <div class="gizmo-modes">
+                {mode_button(GizmoMode::Translate, "Move")}
+                {mode_button(GizmoMode::Rotate, "Rotate")}
+                {mode_button(GizmoMode::Scale, "Scale")}
+            </div>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.get().snap
+                    on:change=move |evt| {
+                        settings.write().snap = event_target_checked(&evt);
+                    }
+                />
+                "Snap"
+            </label>
+            {values}
+        </aside>
+    }
+}
+
+/// The entity tree of the Bevy world, and the components of one entity as form fields.
+#[cfg(target_arch = "wasm32")]
+#[component]
+fn InspectorPanel(
+    snapshot: LeptosEventReceiver<InspectorSnapshot>,
+    edits: LeptosEventSender<InspectorEdit>,
+    selected: RwSignal<Option<Entity>>,
+) -> impl IntoView {
+    // Follows the selection on the canvas, but any entity can be picked from the tree.
+    let inspected = RwSignal::new(None::<Entity>);
+    Effect::new(move || {
+        if let Some(entity) = selected.get() {
+            inspected.set(Some(entity));
+        }
+    });
+
+    let snapshot = Memo::new(move |_| snapshot.get().unwrap_or_default());
+    let edits = StoredValue::new(edits);
+
+    // Keyed lists, so inputs keep focus while new snapshots come in.
+    let tree = Memo::new(move |_| {
+        leptos::prelude::With::with(&snapshot, |InspectorSnapshot(entities)| {
+            entities
+                .iter()
+                .map(|inspected| (inspected.entity, inspected.label.clone(), inspected.depth))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let components = Memo::new(move |_| {
+        let entity = inspected.get()?;
+        leptos::prelude::With::with(&snapshot, |snapshot| {
+            let components = snapshot.entity(entity)?.components.iter();
+            Some(
+                components
+                    .map(|component| {
+                        let paths = component.fields.iter().map(|field| field.path.clone());
+                        (entity, component.kind, paths.collect::<Vec<_>>())
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+    });
+
+    view! {
+        <aside class="inspector">
+            <h3>"Inspector"</h3>
+            <ul class="entity-tree">
+                <For
+                    each=move || tree.get()
+                    key=|entity| entity.clone()
+                    children=move |(entity, label, depth)| {
+                        view! {
+                            <li style:padding-left=format!("{depth}rem")>
+                                <button
+                                    class:active=move || inspected.get() == Some(entity)
+                                    on:click=move |_| inspected.set(Some(entity))
+                                >
+                                    {label}
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+            <Show
+                when=move || leptos::prelude::With::with(&components, Option::is_some)
+                fallback=|| view! { <p>"Pick an entity to inspect it."</p> }
+            >
+                <For
+                    each=move || components.get().unwrap_or_default()
+                    key=|component| component.clone()
+                    children=move |(entity, kind, paths)| {
+                        view! {
+                            <fieldset>
+                                <legend>{kind.label()}</legend>
+                                {paths
+                                    .into_iter()
+                                    .map(|path| {
+                                        let label = path.trim_start_matches('.').to_string();
+                                        let value = Memo::new({
+                                            let path = path.clone();
+                                            move |_| {
+                                                leptos::prelude::With::with(&snapshot, |snapshot| {
+                                                    snapshot.field(entity, kind, &path).cloned()
+                                                })
+                                            }
+                                        });
+                                        let send = move |value| {
+                                            let edit = InspectorEdit {
+                                                entity,
+                                                component: kind,
+                                                path: path.clone(),
+                                                value,
+                                            };
+                                            edits.with_value(|edits| edits.send(edit).ok());
+                                        };
+                                        view! {
+                                            <label class="field">
+                                                <span>{label}</span>
+                                                {field_input(value, send)}
+                                            </label>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </fieldset>
+                        }
+                    }
+                />
+            </Show>
+        </aside>
+    }
+}
+
+/// The input for a field of the inspector, depending on what kind of value it holds.
+#[cfg(target_arch = "wasm32")]
+fn field_input(
+    value: Memo<Option<FieldValue>>,
+    send: impl Fn(FieldValue) + Clone + Send + Sync + 'static,
+) -> AnyView {
+    // Enough digits to edit with, without float noise like 0.30000001.
+    let round = |value: f32, digits: i32| {
+        let scale = 10f32.powi(digits);
+        // Adding zero turns -0 into 0.
+        ((value * scale).round() / scale + 0.0).to_string()
+    };
+    let parse = |evt| event_target_value(&evt).trim().parse::<f32>().ok();
+
+    match value.get_untracked() {
+        Some(FieldValue::Number(_)) => view! {
+            <input
+                type="number"
+                step="any"
+                prop:value=move || match value.get() {
+                    Some(FieldValue::Number(number)) => round(number, 3),
+                    _ => String::new(),
+                }
+                on:change=move |evt| {
+                    if let Some(number) = parse(evt).filter(|number| number.is_finite()) {
+                        send(FieldValue::Number(number));
+                    }
+                }
+            />
+        }
+        .into_any(),
+        Some(FieldValue::Toggle(_)) => view! {
+            <input
+                type="checkbox"
+                prop:checked=move || value.get() == Some(FieldValue::Toggle(true))
+                on:change=move |evt| send(FieldValue::Toggle(event_target_checked(&evt)))
+            />
+        }
+        .into_any(),
+        Some(FieldValue::Rotation(_)) => (0..3)
+            .map(|axis| {
+                let send = send.clone();
+                let degrees = move || match value.get() {
+                    Some(FieldValue::Rotation(degrees)) => Some(degrees),
+                    _ => None,
+                };
+                view! {
+                    <input
+                        type="number"
+                        step="any"
+                        prop:value=move || {
+                            degrees().map(|degrees| round(degrees[axis], 2)).unwrap_or_default()
+                        }
+                        on:change=move |evt| {
+                            let Some(mut degrees) = untrack(degrees) else {
+                                return;
+                            };
+                            if let Some(angle) = parse(evt).filter(|angle| angle.is_finite()) {
+                                degrees[axis] = angle;
+                                send(FieldValue::Rotation(degrees));
+                            }
+                        }
+                    />
+                }
+            })
+            .collect_view()
+            .into_any(),
+        Some(FieldValue::Color(_)) => view! {
+            <input
+                type="color"
+                prop:value=move || match value.get() {
+                    Some(FieldValue::Color(color)) => {
+                        Srgba::from_f32_array(color).with_alpha(1.0).to_hex().to_lowercase()
+                    }
+                    _ => String::new(),
+                }
+                on:input=move |evt| {
+                    let alpha = match value.get_untracked() {
+                        Some(FieldValue::Color([.., alpha])) => alpha,
+                        _ => 1.0,
+                    };
+                    if let Ok(color) = Srgba::hex(event_target_value(&evt)) {
+                        send(FieldValue::Color(color.with_alpha(alpha).to_f32_array()));
+                    }
+                }
+            />
+        }
+        .into_any(),
+        Some(FieldValue::Text(_)) | None => view! {
+            <span class="value">
+                {move || match value.get() {
+                    Some(FieldValue::Text(text)) => text,
+                    _ => String::new(),
+                }}
+            </span>
+        }
+        .into_any(),
+    }
+}
+
+/// Plays the physics simulation of the scene and makes the selected object take part in it.
+#[cfg(target_arch = "wasm32")]
+#[component]
+fn PhysicsControls(
+    state: LeptosEventReceiver<PhysicsState>,
+    commands: LeptosEventSender<PhysicsCommand>,
+) -> impl IntoView {
+    let state = Memo::new(move |_| state.get().unwrap_or_default());
+    let commands = StoredValue::new(commands);
+    let send = move |command| {
+        commands.with_value(|commands| commands.send(command).ok());
+    };
+
+    let body = move || match state.get().selected {
+        Some((_, Some(RigidBody::Static))) => "static",
+        Some((_, Some(RigidBody::Dynamic))) => "dynamic",
+        _ => "none",
+    };
+
+    view! {
+        <div class="toolbar physics-controls">
+            <button on:click=move |_| {
+                send(if state.get_untracked().playing {
+                    PhysicsCommand::Pause
+                } else {
+                    PhysicsCommand::Play
+                })
+            }>{move || if state.get().playing { "Pause" } else { "Play" }}</button>
+            <button
+                disabled=move || state.get().playing
+                on:click=move |_| send(PhysicsCommand::Step)
+            >
+                "Step"
+            </button>
+            <button
+                disabled=move || state.get().steps == 0
+                on:click=move |_| send(PhysicsCommand::Reset)
+            >
+                "Reset"
+            </button>
+            <output>
+                {move || {
+                    let steps = state.get().steps;
+                    format!("Step {steps} ({:.2} s)", steps as f32 * TIME_STEP)
+                }}
+            </output>
+            <label>
+                "Body "
+                <select
+                    disabled=move || state.get().selected.is_none()
+                    prop:value=body
+                    on:change=move |evt| {
+                        let Some((entity, _)) = state.get_untracked().selected else {
+                            return;
+                        };
+                        let body = match event_target_value(&evt).as_str() {
+                            "static" => Some(RigidBody::Static),
+                            "dynamic" => Some(RigidBody::Dynamic),
+                            _ => None,
+                        };
+                        send(PhysicsCommand::SetBody { entity, body });
+                    }
+                >
+                    <option value="none">"None"</option>
+                    <option value="static">"Static"</option>
+                    <option value="dynamic">"Dynamic"</option>
+                </select>
+            </label>
+        </div>
+    }
+}
+
+/// The material of the selected object, previewed on the canvas while it is edited.
+///
+/// Objects share a material by giving theirs the same name.
+#[cfg(target_arch = "wasm32")]
+#[component]
+fn MaterialPanel(
+    selected: LeptosEventReceiver<SelectedMaterial>,
+    edits: LeptosEventSender<MaterialEdit>,
+) -> impl IntoView {
+    let selected = Memo::new(move |_| selected.get().unwrap_or_default());
+    let material = Memo::new(move |_| selected.get().material);
+    let edits = StoredValue::new(edits);
+
+    // Every change is sent right away; the history merges quick changes into one edit.
+    let edit = move |change: &dyn Fn(&mut MaterialDescription)| {
+        let SelectedMaterial {
+            entity: Some(entity),
+            material: Some(mut material),
+            ..
+        } = selected.get_untracked()
+        else {
+            return;
+        };
+        change(&mut material);
+        edits.with_value(|edits| edits.send(MaterialEdit { entity, material }).ok());
+    };
+
+    let slider = move |label: &'static str,
+                       get: fn(&MaterialDescription) -> f32,
+                       set: fn(&mut MaterialDescription, f32)| {
+        let value = move || material.get().as_ref().map(get).unwrap_or_default();
+        view! {
+            <label class="field">
+                <span>{label}</span>
+                <input
+                    type="range"
+                    min="0"
+                    max="1"
+                    step="0.01"
+                    prop:value=move || value().to_string()
+                    on:input=move |evt| {
+                        if let Some(value) = parse_number(&evt) {
+                            edit(&|material| set(material, value.clamp(0.0, 1.0)));
+                        }
+                    }
+                />
+                // Hidden from the accessible name, which is just the name of the property.
+                <output aria-hidden="true">{move || format!("{:.2}", value())}</output>
+            </label>
+        }
+    };
+
+    let alpha_mode = move || {
+        material
+            .get()
+            .and_then(|material| material.alpha_mode)
+            .map_or("auto", alpha_mode_key)
+    };
+    let mask_cutoff = move || match material.get().and_then(|material| material.alpha_mode) {
+        Some(AlphaModeDescription::Mask(cutoff)) => Some(cutoff),
+        _ => None,
+    };
+
+    let texture_input = move |slot: TextureSlot| {
+        view! {
+            <label class="field">
+                <span>{slot.label()}</span>
+                <input
+                    type="text"
+                    list="bundled-textures"
+                    placeholder="None"
+                    prop:value=move || {
+                        material
+                            .get()
+                            .and_then(|material| material.textures.get(slot).clone())
+                            .unwrap_or_default()
+                    }
+                    on:change=move |evt| {
+                        let path = event_target_value(&evt).trim().to_string();
+                        edit(&|material| {
+                            *material.textures.get_mut(slot) =
+                                Some(path.clone()).filter(|path| !path.is_empty());
+                        });
+                    }
+                />
+            </label>
+        }
+    };
+
+    view! {
+        <aside class="material-panel">
+            <h3>"Material"</h3>
+            <Show
+                when=move || leptos::prelude::With::with(&material, Option::is_some)
+                fallback=|| view! { <p>"Select an object to edit its material."</p> }
+            >
+                <label class="field">
+                    <span>"Name"</span>
+                    <input
+                        type="text"
+                        list="material-names"
+                        placeholder="Unnamed"
+                        prop:value=move || {
+                            material.get().and_then(|material| material.name).unwrap_or_default()
+                        }
+                        on:change=move |evt| {
+                            let name = event_target_value(&evt);
+                            edit(&|material| material.name = Some(name.clone()));
+                        }
+                    />
+                </label>
+                <datalist id="material-names">
+                    {move || {
+                        selected
+                            .get()
+                            .library
+                            .into_iter()
+                            .map(|name| view! { <option value=name></option> })
+                            .collect_view()
+                    }}
+                </datalist>
+                {move || {
+                    let users = selected.get().users;
+                    (users > 1)
+                        .then(|| {
+                            view! { <p class="hint">{format!("Shared by {users} objects")}</p> }
+                        })
+                }}
+                <label class="field">
+                    <span>"Base color"</span>
+                    <input
+                        type="color"
+                        prop:value=move || {
+                            material
+                                .get()
+                                .map(|material| {
+                                    let [red, green, blue, _] = material.base_color;
+                                    color_hex([red, green, blue])
+                                })
+                                .unwrap_or_default()
+                        }
+                        on:input=move |evt| {
+                            if let Some([red, green, blue]) = parse_color(&evt) {
+                                edit(&|material| {
+                                    let alpha = material.base_color[3];
+                                    material.base_color = [red, green, blue, alpha];
+                                });
+                            }
+                        }
+                    />
+                </label>
+                {slider(
+                    "Alpha",
+                    |material| material.base_color[3],
+                    |material, alpha| material.base_color[3] = alpha,
+                )}
+                {slider(
+                    "Metallic",
+                    |material| material.metallic,
+                    |material, metallic| material.metallic = metallic,
+                )}
+                {slider(
+                    "Roughness",
+                    |material| material.perceptual_roughness,
+                    |material, roughness| material.perceptual_roughness = roughness,
+                )}
+                {slider(
+                    "Reflectance",
+                    |material| material.reflectance,
+                    |material, reflectance| material.reflectance = reflectance,
+                )}
+                <label class="field">
+                    <span>"Emissive"</span>
+                    <input
+                        type="color"
+                        prop:value=move || {
+                            material
+                                .get()
+                                .map(|material| color_hex(material.emissive))
+                                .unwrap_or_default()
+                        }
+                        on:input=move |evt| {
+                            if let Some(emissive) = parse_color(&evt) {
+                                edit(&|material| material.emissive = emissive);
+                            }
+                        }
+                    />
+                </label>
+                <label class="field">
+                    <span>"Alpha mode"</span>
+                    <select
+                        prop:value=alpha_mode
+                        on:change=move |evt| {
+                            let key = event_target_value(&evt);
+                            edit(&|material| {
+                                material.alpha_mode = alpha_mode_from_key(&key);
+                            });
+                        }
+                    >
+                        <option value="auto">"Automatic"</option>
+                        <option value="opaque">"Opaque"</option>
+                        <option value="mask">"Mask"</option>
+                        <option value="blend">"Blend"</option>
+                        <option value="premultiplied">"Premultiplied"</option>
+                        <option value="add">"Add"</option>
+                        <option value="multiply">"Multiply"</option>
+                    </select>
+                </label>
+                {move || {
+                    mask_cutoff()
+                        .map(|cutoff| {
+                            view! {
+                                <label class="field">
+                                    <span>"Alpha cutoff"</span>
+                                    <input
+                                        type="number"
+                                        min="0"
+                                        max="1"
+                                        step="0.05"
+                                        prop:value=cutoff.to_string()
+                                        on:change=move |evt| {
+                                            if let Some(cutoff) = parse_number(&evt) {
+                                                edit(&|material| {
+                                                    material.alpha_mode = Some(
+                                                        AlphaModeDescription::Mask(cutoff),
+                                                    );
+                                                });
+                                            }
+                                        }
+                                    />
+                                </label>
+                            }
+                        })
+                }}
+                <fieldset>
+                    <legend>"Textures"</legend>
+                    {TextureSlot::ALL.map(texture_input).collect_view()}
+                </fieldset>
+                <datalist id="bundled-textures">
+                    {BUNDLED_TEXTURES
+                        .map(|path| view! { <option value=path></option> })
+                        .collect_view()}
+                </datalist>
+            </Show>
+        </aside>
+    }
+}
+
+/// The value of an alpha mode in the select of the `MaterialPanel`.
+#[cfg(target_arch = "wasm32")]
+fn alpha_mode_key(alpha_mode: AlphaModeDescription) -> &'static str {
+    match alpha_mode {
+        AlphaModeDescription::Opaque => "opaque",
+        AlphaModeDescription::Mask(_) => "mask",
+        AlphaModeDescription::Blend => "blend",
+        AlphaModeDescription::Premultiplied => "premultiplied",
+        AlphaModeDescription::Add => "add",
+        AlphaModeDescription::Multiply => "multiply",
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn alpha_mode_from_key(key: &str) -> Option<AlphaModeDescription> {
+    Some(match key {
+        "opaque" => AlphaModeDescription::Opaque,
+        "mask" => AlphaModeDescription::Mask(0.5),
+        "blend" => AlphaModeDescription::Blend,
+        "premultiplied" => AlphaModeDescription::Premultiplied,
+        "add" => AlphaModeDescription::Add,
+        "multiply" => AlphaModeDescription::Multiply,
+        _ => return None,
+    })
+}
+
+/// Lights of the scene and the light around it, previewed on the canvas while they are edited.
+#[cfg(target_arch = "wasm32")]
+#[component]
+fn LightingPanel(
+    state: LeptosEventReceiver<LightingState>,
+    commands: LeptosEventSender<LightingCommand>,
+) -> impl IntoView {
+    let state = Memo::new(move |_| state.get().unwrap_or_default());
+    let environment = Memo::new(move |_| state.get().environment);
+    let commands = StoredValue::new(commands);
+    let send = move |command| {
+        commands.with_value(|commands| commands.send(command).ok());
+    };
+
+    // Lights are numbered by kind, in the order of the scene.
+    let lights = move || {
+        let lights = state.get().lights;
+        lights
+            .iter()
+            .enumerate()
+            .map(|(index, (entity, light))| {
+                let kind = LightKind::of(light);
+                let number = lights[..index]
+                    .iter()
+                    .filter(|(_, other)| LightKind::of(other) == kind)
+                    .count()
+                    + 1;
+                (*entity, format!("{} {number}", kind.label()))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let light_fields = move |entity: Entity, label: String| {
+        let light = Memo::new(move |_| {
+            state
+                .get()
+                .lights
+                .into_iter()
+                .find(|(other, _)| *other == entity)
+                .map(|(_, light)| light)
+        });
+        let edit = move |change: &dyn Fn(&mut LightDescription)| {
+            if let Some(mut light) = light.get_untracked() {
+                change(&mut light);
+                send(LightingCommand::Edit { entity, light });
+            }
+        };
+        // The kind of a light never changes, so neither do its fields.
+        let fields = light
+            .get_untracked()
+            .map(|mut light| {
+                LIGHT_FIELDS
+                    .into_iter()
+                    .filter(|(_, _, field)| field(&mut light).is_some())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        view! {
+            <fieldset class="light">
+                <legend>{label}</legend>
+                {color_field(
+                    "Color",
+                    move || light.get().map(|mut light| *light.color_mut()),
+                    move |color| edit(&|light| *light.color_mut() = color),
+                )}
+                {fields
+                    .into_iter()
+                    .map(|(label, step, field)| {
+                        number_field(
+                            label,
+                            step,
+                            move || light.get().and_then(|mut light| field(&mut light).copied()),
+                            move |number| {
+                                edit(&|light| {
+                                    if let Some(value) = field(light) {
+                                        *value = number;
+                                    }
+                                })
+                            },
+                        )
+                    })
+                    .collect_view()}
+                <label class="field">
+                    <span>"Shadows"</span>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || {
+                            light.get().is_some_and(|mut light| *light.shadows_mut())
+                        }
+                        on:change=move |evt| {
+                            let shadows = event_target_checked(&evt);
+                            edit(&|light| *light.shadows_mut() = shadows);
+                        }
+                    />
+                </label>
+                <button on:click=move |_| send(LightingCommand::Remove(entity))>"Remove"</button>
+            </fieldset>
+        }
+    };
+
+    let edit_environment = move |change: &dyn Fn(&mut EnvironmentDescription)| {
+        let mut environment = environment.get_untracked();
+        change(&mut environment);
+        send(LightingCommand::Environment(environment));
+    };
+    // Only whether these are on decides what is shown, so fields stay put while they are edited.
+    let ambient_on = Memo::new(move |_| environment.get().ambient.is_some());
+    let sky_on = Memo::new(move |_| environment.get().environment_map.is_some());
+
+    let ambient_fields = move || {
+        let ambient = move || environment.get().ambient;
+        let edit_ambient = move |change: &dyn Fn(&mut AmbientDescription)| {
+            edit_environment(&|environment| {
+                if let Some(ambient) = &mut environment.ambient {
+                    change(ambient);
+                }
+            })
+        };
+        view! {
+            {color_field(
+                "Ambient color",
+                move || ambient().map(|ambient| ambient.color),
+                move |color| edit_ambient(&|ambient| ambient.color = color),
+            )}
+            {number_field(
+                "Brightness (cd/m²)",
+                "10",
+                move || ambient().map(|ambient| ambient.brightness),
+                move |brightness| edit_ambient(&|ambient| ambient.brightness = brightness),
+            )}
+        }
+    };
+
+    let sky_fields = move || {
+        let sky = move || environment.get().environment_map;
+        let edit_sky = move |change: &dyn Fn(&mut EnvironmentMapDescription)| {
+            edit_environment(&|environment| {
+                if let Some(sky) = &mut environment.environment_map {
+                    change(sky);
+                }
+            })
+        };
+        view! {
+            {color_field(
+                "Sky",
+                move || sky().map(|sky| sky.sky),
+                move |color| edit_sky(&|sky| sky.sky = color),
+            )}
+            {color_field(
+                "Horizon",
+                move || sky().map(|sky| sky.horizon),
+                move |color| edit_sky(&|sky| sky.horizon = color),
+            )}
+            {color_field(
+                "Ground",
+                move || sky().map(|sky| sky.ground),
+                move |color| edit_sky(&|sky| sky.ground = color),
+            )}
+            {number_field(
+                "Intensity (cd/m²)",
+                "50",
+                move || sky().map(|sky| sky.intensity),
+                move |intensity| edit_sky(&|sky| sky.intensity = intensity),
+            )}
+        }
+    };
+
+    view! {
+        <aside class="lighting-panel">
+            <h3>"Lighting"</h3>
+            <div class="add-lights">
+                {LightKind::ALL
+                    .map(|kind| {
+                        view! {
+                            <button on:click=move |_| {
+                                send(LightingCommand::Add(kind))
+                            }>{format!("Add {}", kind.label().to_lowercase())}</button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+            <For each=lights key=|light| light.clone() let:light>
+                {light_fields(light.0, light.1)}
+            </For>
+            <fieldset>
+                <legend>"Environment"</legend>
+                <label class="field">
+                    <span>"Ambient light"</span>
+                    <input
+                        type="checkbox"
+                        prop:checked=ambient_on
+                        on:change=move |evt| {
+                            let on = event_target_checked(&evt);
+                            edit_environment(&|environment| {
+                                environment.ambient = on.then(AmbientDescription::default);
+                            });
+                        }
+                    />
+                </label>
+                <Show when=move || ambient_on.get()>{ambient_fields}</Show>
+                <label class="field">
+                    <span>"Environment map"</span>
+                    <input
+                        type="checkbox"
+                        prop:checked=sky_on
+                        on:change=move |evt| {
+                            let on = event_target_checked(&evt);
+                            edit_environment(&|environment| {
+                                environment.environment_map =
+                                    on.then(EnvironmentMapDescription::default);
+                            });
+                        }
+                    />
+                </label>
+                <Show when=move || sky_on.get()>{sky_fields}</Show>
+                <label class="field">
+                    <span>"Shadow quality"</span>
+                    <select
+                        prop:value=move || environment.get().shadow_quality.label()
+                        on:change=move |evt| {
+                            let label = event_target_value(&evt);
+                            if let Some(quality) = ShadowQuality::ALL
+                                .into_iter()
+                                .find(|quality| quality.label() == label)
+                            {
+                                edit_environment(&|environment| {
+                                    environment.shadow_quality = quality;
+                                });
+                            }
+                        }
+                    >
+                        {ShadowQuality::ALL
+                            .map(|quality| {
+                                view! { <option value=quality.label()>{quality.label()}</option> }
+                            })
+                            .collect_view()}
+                    </select>
+                </label>
+            </fieldset>
+        </aside>
+    }
+}
+
+/// A number of a light in the `LightingPanel`, as its label, its step and where it is kept.
+#[cfg(target_arch = "wasm32")]
+type LightField = (
+    &'static str,
+    &'static str,
+    fn(&mut LightDescription) -> Option<&mut f32>,
+);
+
+#[cfg(target_arch = "wasm32")]
+const LIGHT_FIELDS: [LightField; 5] = [
+    ("Intensity (lm)", "100", |light| match light {
+        LightDescription::Point { intensity, .. } | LightDescription::Spot { intensity, .. } => {
+            Some(intensity)
+        }
+        LightDescription::Directional { .. } => None,
+    }),
+    ("Illuminance (lx)", "100", |light| match light {
+        LightDescription::Directional { illuminance, .. } => Some(illuminance),
+        _ => None,
+    }),
+    ("Range (m)", "1", |light| match light {
+        LightDescription::Point { range, .. } | LightDescription::Spot { range, .. } => {
+            Some(range)
+        }
+        LightDescription::Directional { .. } => None,
+    }),
+    ("Inner angle (°)", "1", |light| match light {
+        LightDescription::Spot { inner_angle, .. } => Some(inner_angle),
+        _ => None,
+    }),
+    ("Outer angle (°)", "1", |light| match light {
+        LightDescription::Spot { outer_angle, .. } => Some(outer_angle),
+        _ => None,
+    }),
+];
+
+/// A labelled color input.
+#[cfg(target_arch = "wasm32")]
+fn color_field(
+    label: &'static str,
+    value: impl Fn() -> Option<[f32; 3]> + Send + Sync + 'static,
+    set: impl Fn([f32; 3]) + 'static,
+) -> impl IntoView {
+    view! {
+        <label class="field">
+            <span>{label}</span>
+            <input
+                type="color"
+                prop:value=move || value().map(color_hex).unwrap_or_default()
+                on:input=move |evt| {
+                    if let Some(color) = parse_color(&evt) {
+                        set(color);
+                    }
+                }
+            />
+        </label>
+    }
+}
+
+/// A labelled input for a number that can't be negative.
+#[cfg(target_arch = "wasm32")]
+fn number_field(
+    label: &'static str,
+    step: &'static str,
+    value: impl Fn() -> Option<f32> + Send + Sync + 'static,
+    set: impl Fn(f32) + 'static,
+) -> impl IntoView {
+    view! {
+        <label class="field">
+            <span>{label}</span>
+            <input
+                type="number"
+                min="0"
+                step=step
+                prop:value=move || value().map(|value| value.to_string()).unwrap_or_default()
+                on:change=move |evt| {
+                    if let Some(number) = parse_number(&evt) {
+                        set(number.max(0.0));
+                    }
+                }
+            />
+        </label>
+    }
+}
+
+/// An sRGB color as the value of a color input.
+#[cfg(target_arch = "wasm32")]
+fn color_hex([red, green, blue]: [f32; 3]) -> String {
+    Srgba::rgb(red, green, blue).to_hex().to_lowercase()
+}
+
+/// The sRGB color of a color input.
+#[cfg(target_arch = "wasm32")]
+fn parse_color(evt: &web_sys::Event) -> Option<[f32; 3]> {
+    Srgba::hex(event_target_value(evt))
+        .ok()
+        .map(|color| color.to_f32_array_no_alpha())
+}
+
+/// The finite number in an input, if it holds one.
+#[cfg(target_arch = "wasm32")]
+fn parse_number(evt: &web_sys::Event) -> Option<f32> {
+    event_target_value(evt)
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// Saves the scene on the canvas and loads stored scenes back into it.
+#[cfg(target_arch = "wasm32")]
+#[component]
+fn SceneLibrary(
+    commands: LeptosEventSender<SceneCommand>,
+    snapshots: LeptosEventReceiver<SceneSnapshot>,
+) -> impl IntoView {
+    let title = RwSignal::new(String::new());
+    let save = ServerAction::<SaveScene>::new();
+    let delete = ServerAction::<DeleteScene>::new();
+    let load = Action::new_local(|id: &String| load_scene(id.clone()));
+    let scenes = leptos::prelude::Resource::new(
+        move || (save.version().get(), delete.version().get()),
+        |_| list_scenes(),
+    );
+
+    // Saving asks Bevy for a snapshot first, which is stored once it arrives.
+    Effect::new(move || {
+        if let Some(SceneSnapshot(mut scene)) = snapshots.get() {
+            let title = title.get_untracked();
+            if !title.is_empty() {
+                scene.title = title;
+            }
+            save.dispatch(SaveScene { scene });
+        }
+    });
+
+    Effect::new({
+        let commands = commands.clone();
+        move || {
+            if let Some(Ok(Some(scene))) = load.value().get() {
+                commands.send(SceneCommand::Load(scene)).ok();
+            }
+        }
+    });
+
+    let error = move || {
+        let error = match (save.value().get(), load.value().get(), delete.value().get()) {
+            (Some(Err(err)), _, _) => format!("Saving failed: {err}"),
+            (_, Some(Err(err)), _) => format!("Loading failed: {err}"),
+            (_, Some(Ok(None)), _) => "This scene no longer exists.".to_string(),
+            (_, _, Some(Err(err))) => format!("Deleting failed: {err}"),
+            _ => return None,
+        };
+        Some(view! { <p class="error">{error}</p> })
+    };
+
+    let scene_list = move || {
+        scenes.get().map(|scenes| match scenes {
+            Ok(scenes) if scenes.is_empty() => view! { <p>"No saved scenes yet."</p> }.into_any(),
+            Ok(scenes) => view! {
+                <ul class="scene-list">
+                    {scenes
+                        .into_iter()
+                        .map(|scene| {
+                            let load_id = scene.id.clone();
+                            let delete_id = scene.id.clone();
+                            view! {
+                                <li>
+                                    <img
+                                        class="thumbnail"
+                                        src=format!("/thumb/{}.png", scene.id)
+                                        alt=""
+                                    />
+                                    <a href=format!("/scene/{}", scene.id)>
+                                        {if scene.title.is_empty() {
+                                            "Untitled".to_string()
+                                        } else {
+                                            scene.title
+                                        }}
+                                    </a>
+                                    <button on:click=move |_| {
+                                        load.dispatch_local(load_id.clone());
+                                    }>"Load"</button>
+                                    <button on:click=move |_| {
+                                        delete.dispatch(DeleteScene { id: delete_id.clone() });
+                                    }>"Delete"</button>
+                                </li>
+                            }
+                        })
+                        .collect_view()}
+                </ul>
+            }
+            .into_any(),
+            Err(err) => view! { <p class="error">"Listing scenes failed: " {err.to_string()}</p> }
+                .into_any(),
+        })
+    };
+
+    view! {
+        <section class="scene-library">
+            <h3>"Saved scenes"</h3>
+            <input
+                type="text"
+                placeholder="Scene title"
+                prop:value=title
+                on:input=move |evt| title.set(event_target_value(&evt))
+            />
+            <button on:click=move |_| {
+                commands.send(SceneCommand::Snapshot).ok();
+            }>"Save scene"</button>
+            {error}
+            <Transition fallback=|| view! { <p>"Loading scenes..."</p> }>{scene_list}</Transition>
+        </section>
     }
 }
 
@@ -107,61 +1511,157 @@ fn CanvasPage() -> impl IntoView {
     }
 }
 
+/// -------- Leptos Viewports --------
+#[component]
+fn ViewportsPage() -> impl IntoView {
+    // Explicit ids, so the server-rendered canvases are the ones Bevy looks for after hydration.
+    view! {
+        <h2>"Scene Viewports"</h2>
+        <div class="canvas-row">
+            <SceneView
+                canvas_id="main_viewport"
+                plugins=|app: &mut App| {
+                    app.add_systems(Startup, setup_scene);
+                }
+            >
+                <SceneViewport
+                    canvas_id="preview_viewport"
+                    fit=CanvasFit::Fixed {
+                        width: 240.0,
+                        height: 240.0,
+                    }
+                    camera=Transform::from_xyz(0.0, 8.0, 0.01).looking_at(Vec3::ZERO, Vec3::Y)
+                />
+            </SceneView>
+            // A second app with a scene of its own, rendered offscreen with the GPU of the first.
+            <SceneView
+                canvas_id="second_scene"
+                fit=CanvasFit::Fixed {
+                    width: 320.0,
+                    height: 240.0,
+                }
+                background=Color::srgb(0.1, 0.12, 0.18)
+                plugins=|app: &mut App| {
+                    app.add_systems(Startup, setup_scene);
+                }
+            />
+        </div>
+    }
+}
+
+/// -------- Leptos Stored Scene --------
+#[component]
+fn ScenePage() -> impl IntoView {
+    let params = use_params_map();
+    // Blocking, so the title and metadata of the scene end up in the server-rendered head.
+    let scene = leptos::prelude::Resource::new_blocking(
+        move || params.read().get("id").unwrap_or_default(),
+        load_scene,
+    );
+
+    view! {
+        <Suspense fallback=|| view! { <p>"Loading scene..."</p> }>
+            {move || Suspend::new(async move {
+                match scene.await {
+                    Ok(Some(scene)) => {
+                        let id = params.read_untracked().get("id").unwrap_or_default();
+                        view! { <StoredScene id scene /> }.into_any()
+                    }
+                    Ok(None) => view! { <NotFound /> }.into_any(),
+                    Err(err) => {
+                        view! { <p class="error">"Loading the scene failed: " {err.to_string()}</p> }
+                            .into_any()
+                    }
+                }
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn StoredScene(id: String, scene: SceneDescription) -> impl IntoView {
+    let title = if scene.title.is_empty() {
+        "Untitled scene".to_string()
+    } else {
+        scene.title.clone()
+    };
+    let description = format!(
+        "A scene with {} objects and {} lights.",
+        scene.objects.len(),
+        scene.lights.len()
+    );
+
+    view! {
+        <Title text=title.clone() />
+        <Meta name="description" content=description.clone() />
+        <Meta property="og:title" content=title.clone() />
+        <Meta property="og:description" content=description />
+        <Meta property="og:image" content=format!("/thumb/{id}.png") />
+        <h2>{title}</h2>
+        <SceneView
+            canvas_id="stored_scene"
+            plugins=move |app: &mut App| {
+                app.add_systems(Startup, spawn_scene(scene));
+            }
+        />
+        <p>
+            <a href="/canvas">"Back to the canvas"</a>
+        </p>
+    }
+}
+
 // -------- Bevy Systems --------
-pub fn set_text(mut event_reader: EventReader<TextEvent>) {
+pub fn set_text(
+    mut event_reader: EventReader<TextEvent>,
+    labels: Query<Entity, bevy::prelude::With<SceneText>>,
+    mut edits: EventWriter<EditCommand>,
+) {
     for event in event_reader.read() {
         info!("Got text from Leptos: {}", event.text);
+
+        for entity in &labels {
+            edits.write(EditCommand {
+                entity,
+                edit: SceneEdit::Text(event.text.clone()),
+            });
+        }
+    }
+}
+
+/// Spawns a scene that was handed to the app rather than loaded from a file.
+fn spawn_scene(scene: SceneDescription) -> impl FnMut(Commands, ResMut<Assets<SceneDescription>>) {
+    move |mut commands, mut descriptions| {
+        commands.spawn(DescribedScene(descriptions.add(scene.clone())));
     }
 }
 
-fn setup_scene(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+/// The scene shown on the canvas pages, served from `public/`.
+pub const DEFAULT_SCENE: &str = "scenes/default.scene.ron";
+
+fn setup_scene(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(DescribedScene(asset_server.load(DEFAULT_SCENE)));
+}
+
+/// Set up the Bevy app that runs inside the Leptos canvas
+#[cfg(target_arch = "wasm32")]
+fn init_bevy_app(
+    app: &mut App,
+    receiver: BevyEventReceiver<TextEvent>,
+    sender: BevyEventSender<SceneEvent>,
+    cube_color: BevyEventDuplex<CubeColor>,
+    camera_commands: BevyEventReceiver<CameraCommand>,
+    scene_commands: BevyEventReceiver<SceneCommand>,
+    snapshots: BevyEventSender<SceneSnapshot>,
 ) {
-    // Cube
-    commands.spawn((
-        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.3, 0.6, 0.9),
-            ..default()
-        })),
-        Transform::from_xyz(0.0, 0.5, 0.0),
-    ));
-
-    // Light
-    commands.spawn((
-        PointLight {
-            intensity: 1500.0,
-            shadows_enabled: true,
-            range: 20.0,
-            ..default()
-        },
-        Transform::from_xyz(4.0, 8.0, 4.0),
-    ));
-
-    // Camera
-    commands.spawn((
-        Camera3d::default(),
-        Transform::from_xyz(3.0, 3.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
-    ));
-}
-
-/// Initialize the Bevy app that runs inside the Leptos canvas
-#[cfg(target_arch = "wasm32")]
-fn init_bevy_app(receiver: BevyEventReceiver<TextEvent>) -> App {
-    let mut app = App::new();
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            canvas: Some("#bevy_canvas".into()),
-            resolution: (400., 300.).into(),
-            ..default()
-        }),
-        ..default()
-    }))
-    .import_event_from_leptos(receiver)
-    .add_systems(Startup, setup_scene)
-    .add_systems(Update, set_text);
-
-    app
+    app.import_event_from_leptos(receiver)
+        .export_event_to_leptos(sender)
+        .sync_leptos_signal_with_resource(cube_color)
+        .import_event_from_leptos(camera_commands)
+        .import_event_from_leptos(scene_commands)
+        .export_event_to_leptos(snapshots)
+        .add_systems(Startup, setup_scene)
+        .add_systems(
+            Update,
+            (set_text, move_cube_with_keys.run_if(canvas_focused)),
+        );
 }
@@ -0,0 +1,1160 @@
+use std::time::Duration;
+
+use bevy::ecs::event::EventCursor;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use serde::{Deserialize, Serialize};
+
+use crate::cube_color::{Cube, CubeColor};
+use crate::physics::{PhysicsCommand, PhysicsSimulation, Playback};
+use crate::scene_description::{
+    spawn_object, DescribedObjectIds, DescribedScene, DescribedSceneSpawned, MaterialDescription,
+    ObjectAssets, ObjectDescription, ObjectIds, RigidBody, SceneCommand, SceneDescription,
+    SceneObject, SceneReader, TransformDescription,
+};
+
+/// A change to the scene that is shared with everyone in the room.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SceneMutation {
+    Transform {
+        object: u64,
+        transform: TransformDescription,
+    },
+    Material {
+        object: u64,
+        material: MaterialDescription,
+    },
+    /// Whether and how the object takes part in physics. Where it moves while the physics plays
+    /// isn't shared, everyone simulates it on their own; in rooms simulated on the server, its
+    /// snapshots correct where bodies went.
+    Body {
+        object: u64,
+        body: Option<RigidBody>,
+    },
+    /// Plays, pauses, steps or resets the physics of everyone in the room.
+    Physics(Playback),
+    Spawn {
+        object: u64,
+        description: ObjectDescription,
+    },
+    Despawn {
+        object: u64,
+    },
+    /// Replaces the whole scene.
+    Load(SceneDescription),
+}
+
+/// Messages exchanged with the room relay, as JSON text frames.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RoomMessage {
+    /// Sent by the relay to a client that just connected, with the id it has in the room.
+    Welcome { client: u32 },
+    /// Sent by the relay to the longest connected client of a room when another one joins.
+    Joined { client: u32 },
+    /// The current scene, sent to the client that just joined.
+    Sync {
+        client: u32,
+        scene: SceneDescription,
+        /// The ids of the objects of `scene`, in the same order.
+        #[serde(default)]
+        objects: Vec<u64>,
+    },
+    /// A change made by `client`, relayed to everyone else in the room.
+    Mutation {
+        client: u32,
+        mutation: Box<SceneMutation>,
+    },
+    /// The state of the objects in a room whose scene is simulated on the server.
+    Snapshot { objects: Vec<ObjectSnapshot> },
+}
+
+/// What the server simulation holds for one object.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ObjectSnapshot {
+    pub object: u64,
+    pub transform: TransformDescription,
+    pub material: MaterialDescription,
+}
+
+/// A message that arrived from the room relay.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct IncomingRoomMessage(pub RoomMessage);
+
+/// A message to send to the room relay.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct OutgoingRoomMessage(pub RoomMessage);
+
+/// What everyone in the room last heard about an object.
+#[derive(Component, Clone, Debug, PartialEq)]
+struct SyncedState {
+    transform: TransformDescription,
+    material: MaterialDescription,
+    body: Option<RigidBody>,
+    /// When a local edit of the object was last sent, as `Time::elapsed`.
+    last_sent: Option<Duration>,
+}
+
+impl SyncedState {
+    fn new(description: &ObjectDescription) -> Self {
+        Self {
+            transform: description.transform,
+            material: description.material.clone(),
+            body: description.body,
+            last_sent: None,
+        }
+    }
+}
+
+/// How often local edits are sent to the room, at most.
+const BROADCAST_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long snapshots leave an object alone after it was edited here, so that a snapshot taken
+/// before the edit reached the server doesn't pull it back.
+const SNAPSHOT_GRACE: Duration = Duration::from_millis(500);
+
+/// Shares edits of the scene with everyone else in the same room.
+///
+/// Transforms, materials and bodies of scene objects are compared against what was last sent or
+/// received and broadcast when they differ, so any system editing them takes part without knowing
+/// about the room. Objects spawned through `ObjectIds`, deleted objects and loaded scenes are shared
+/// as well, and so are the `PhysicsCommand`s that play the physics. In rooms simulated on the
+/// server, `RoomMessage::Snapshot`s correct transforms and materials that went astray. In the browser the messages go over a WebSocket to `/ws/{room}`;
+/// elsewhere they are exchanged through `IncomingRoomMessage` and `OutgoingRoomMessage`.
+pub struct CollaborationPlugin {
+    pub room: String,
+}
+
+impl Plugin for CollaborationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<IncomingRoomMessage>()
+            .add_event::<OutgoingRoomMessage>()
+            .add_observer(broadcast_despawn)
+            .add_systems(
+                Update,
+                (
+                    (
+                        apply_welcome,
+                        send_scene_to_new_clients,
+                        apply_remote_edits,
+                        apply_remote_bodies,
+                        apply_snapshots,
+                        apply_remote_structure,
+                    )
+                        .chain(),
+                    broadcast_loaded_scenes,
+                    // Paused bodies are shared after the pause, which stops them everywhere.
+                    share_physics_commands.before(broadcast_local_changes),
+                    broadcast_local_changes.run_if(on_timer(BROADCAST_INTERVAL)),
+                ),
+            );
+
+        #[cfg(target_arch = "wasm32")]
+        app.insert_non_send_resource(socket::RoomSocket::connect(&self.room))
+            .add_systems(PreUpdate, socket::receive_room_messages)
+            .add_systems(PostUpdate, socket::send_room_messages);
+    }
+}
+
+fn send_scene_to_new_clients(
+    mut incoming: EventReader<IncomingRoomMessage>,
+    mut outgoing: EventWriter<OutgoingRoomMessage>,
+    scene: SceneReader,
+) {
+    for IncomingRoomMessage(message) in incoming.read() {
+        if let RoomMessage::Joined { client } = *message {
+            if let Some(description) = scene.read() {
+                outgoing.write(OutgoingRoomMessage(RoomMessage::Sync {
+                    client,
+                    scene: description,
+                    objects: scene.object_ids(),
+                }));
+            }
+        }
+    }
+}
+
+fn apply_welcome(mut incoming: EventReader<IncomingRoomMessage>, mut ids: ResMut<ObjectIds>) {
+    for IncomingRoomMessage(message) in incoming.read() {
+        if let RoomMessage::Welcome { client } = *message {
+            ids.client = client;
+        }
+    }
+}
+
+/// The mutations in messages from the relay.
+fn remote_mutations<'a>(
+    incoming: &'a mut EventReader<IncomingRoomMessage>,
+) -> impl Iterator<Item = SceneMutation> + 'a {
+    incoming
+        .read()
+        .filter_map(|IncomingRoomMessage(message)| match message {
+            RoomMessage::Mutation { mutation, .. } => Some(*mutation.clone()),
+            RoomMessage::Welcome { .. }
+            | RoomMessage::Joined { .. }
+            | RoomMessage::Sync { .. }
+            | RoomMessage::Snapshot { .. } => None,
+        })
+}
+
+fn find_object(objects: &Query<(Entity, &SceneObject)>, id: u64) -> Option<Entity> {
+    objects
+        .iter()
+        .find(|(_, object)| object.id == id)
+        .map(|(entity, _)| entity)
+}
+
+type EditableObject = (
+    &'static mut Transform,
+    &'static mut MeshMaterial3d<StandardMaterial>,
+    Option<&'static mut SyncedState>,
+    Has<Cube>,
+);
+
+fn apply_remote_edits(
+    mut incoming: EventReader<IncomingRoomMessage>,
+    mut assets: ObjectAssets,
+    mut cube_color: Option<ResMut<CubeColor>>,
+    scene_objects: Query<(Entity, &SceneObject)>,
+    mut objects: Query<EditableObject>,
+) {
+    for mutation in remote_mutations(&mut incoming) {
+        match mutation {
+            SceneMutation::Transform { object, transform } => {
+                let Some((mut current, _, synced, _)) = find_object(&scene_objects, object)
+                    .and_then(|entity| objects.get_mut(entity).ok())
+                else {
+                    continue;
+                };
+
+                *current = Transform::from(transform);
+                if let Some(mut synced) = synced {
+                    synced.transform = transform;
+                }
+            }
+            SceneMutation::Material { object, material } => {
+                let Some((_, mut handle, synced, is_cube)) = find_object(&scene_objects, object)
+                    .and_then(|entity| objects.get_mut(entity).ok())
+                else {
+                    continue;
+                };
+
+                set_material(
+                    &mut assets,
+                    &mut handle,
+                    is_cube,
+                    &mut cube_color,
+                    &material,
+                );
+                if let Some(mut synced) = synced {
+                    synced.material = material;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_remote_bodies(
+    mut incoming: EventReader<IncomingRoomMessage>,
+    mut objects: Query<(&mut SceneObject, Option<&mut SyncedState>)>,
+) {
+    for mutation in remote_mutations(&mut incoming) {
+        let SceneMutation::Body { object, body } = mutation else {
+            continue;
+        };
+        let Some((mut current, synced)) = objects.iter_mut().find(|(other, _)| other.id == object)
+        else {
+            continue;
+        };
+
+        current.body = body;
+        if let Some(mut synced) = synced {
+            synced.body = body;
+        }
+    }
+}
+
+fn set_material(
+    assets: &mut ObjectAssets,
+    handle: &mut MeshMaterial3d<StandardMaterial>,
+    is_cube: bool,
+    cube_color: &mut Option<ResMut<CubeColor>>,
+    material: &MaterialDescription,
+) {
+    assets.set_material(handle, material);
+    // Keeps the color picker of the page in step with the cube.
+    if let (true, Some(cube_color)) = (is_cube, cube_color.as_mut()) {
+        let [red, green, blue, alpha] = material.base_color;
+        cube_color.0 = Color::srgba(red, green, blue, alpha);
+    }
+}
+
+/// Moves objects to where the server simulation has them.
+///
+/// Only objects that were shared already are corrected, and not while they are being edited here.
+fn apply_snapshots(
+    mut incoming: EventReader<IncomingRoomMessage>,
+    time: Res<Time>,
+    mut assets: ObjectAssets,
+    mut cube_color: Option<ResMut<CubeColor>>,
+    scene_objects: Query<(Entity, &SceneObject)>,
+    mut objects: Query<EditableObject>,
+) {
+    for IncomingRoomMessage(message) in incoming.read() {
+        let RoomMessage::Snapshot { objects: snapshots } = message else {
+            continue;
+        };
+
+        for snapshot in snapshots {
+            let Some((mut current, mut handle, Some(mut synced), is_cube)) =
+                find_object(&scene_objects, snapshot.object)
+                    .and_then(|entity| objects.get_mut(entity).ok())
+            else {
+                continue;
+            };
+            let edited_here = synced
+                .last_sent
+                .is_some_and(|sent| time.elapsed().saturating_sub(sent) < SNAPSHOT_GRACE);
+            if edited_here {
+                continue;
+            }
+
+            if synced.transform != snapshot.transform {
+                *current = Transform::from(snapshot.transform);
+                synced.transform = snapshot.transform;
+            }
+            if synced.material != snapshot.material {
+                set_material(
+                    &mut assets,
+                    &mut handle,
+                    is_cube,
+                    &mut cube_color,
+                    &snapshot.material,
+                );
+                synced.material = snapshot.material.clone();
+            }
+        }
+    }
+}
+
+fn apply_remote_structure(
+    mut commands: Commands,
+    mut incoming: EventReader<IncomingRoomMessage>,
+    mut descriptions: ResMut<Assets<SceneDescription>>,
+    mut assets: ObjectAssets,
+    scenes: Query<(Entity, &DescribedScene)>,
+    scene_objects: Query<(Entity, &SceneObject)>,
+) {
+    for IncomingRoomMessage(message) in incoming.read() {
+        let mutation = match message {
+            // A scene sent to a new client is loaded like any other, but its objects keep their ids
+            // even if they were added to it later.
+            RoomMessage::Sync { scene, objects, .. } => {
+                for (entity, described) in &scenes {
+                    descriptions.insert(&described.0, scene.clone());
+                    commands
+                        .entity(entity)
+                        .insert(DescribedObjectIds(objects.clone()));
+                }
+                continue;
+            }
+            RoomMessage::Mutation { mutation, .. } => mutation.as_ref().clone(),
+            RoomMessage::Welcome { .. }
+            | RoomMessage::Joined { .. }
+            | RoomMessage::Snapshot { .. } => continue,
+        };
+
+        match mutation {
+            SceneMutation::Spawn {
+                object,
+                description,
+            } => {
+                let Some((scene, _)) = scenes.iter().next() else {
+                    continue;
+                };
+
+                let entity = spawn_object(&mut commands, scene, object, &description, &mut assets);
+                commands
+                    .entity(entity)
+                    .insert(SyncedState::new(&description));
+            }
+            SceneMutation::Despawn { object } => {
+                if let Some(entity) = find_object(&scene_objects, object) {
+                    // Without its synced state the removal is not broadcast again.
+                    commands.entity(entity).remove::<SyncedState>().despawn();
+                }
+            }
+            SceneMutation::Load(scene) => {
+                for (entity, described) in &scenes {
+                    descriptions.insert(&described.0, scene.clone());
+                    commands.entity(entity).remove::<DescribedObjectIds>();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn broadcast_loaded_scenes(
+    mut commands: EventReader<SceneCommand>,
+    mut outgoing: EventWriter<OutgoingRoomMessage>,
+    ids: Res<ObjectIds>,
+) {
+    for command in commands.read() {
+        if let SceneCommand::Load(scene) = command {
+            outgoing.write(OutgoingRoomMessage(RoomMessage::Mutation {
+                client: ids.client,
+                mutation: Box::new(SceneMutation::Load(scene.clone())),
+            }));
+        }
+    }
+}
+
+/// Sends the physics commands given here to the room, and gives the ones from the room to the
+/// physics without sending them back.
+fn share_physics_commands(
+    mut incoming: EventReader<IncomingRoomMessage>,
+    mut outgoing: EventWriter<OutgoingRoomMessage>,
+    ids: Res<ObjectIds>,
+    physics: Option<ResMut<Events<PhysicsCommand>>>,
+    mut shared: Local<EventCursor<PhysicsCommand>>,
+) {
+    let Some(mut physics) = physics else {
+        return;
+    };
+
+    for playback in shared
+        .read(&physics)
+        .filter_map(|command| command.playback())
+    {
+        outgoing.write(OutgoingRoomMessage(RoomMessage::Mutation {
+            client: ids.client,
+            mutation: Box::new(SceneMutation::Physics(playback)),
+        }));
+    }
+
+    for mutation in remote_mutations(&mut incoming) {
+        if let SceneMutation::Physics(playback) = mutation {
+            physics.send(playback.into());
+        }
+    }
+    shared.clear(&physics);
+}
+
+fn broadcast_local_changes(
+    mut commands: Commands,
+    mut outgoing: EventWriter<OutgoingRoomMessage>,
+    ids: Res<ObjectIds>,
+    time: Res<Time>,
+    scene: SceneReader,
+    physics: Option<Res<PhysicsSimulation>>,
+    mut objects: Query<(Entity, &SceneObject, Option<&mut SyncedState>)>,
+) {
+    let playing = physics.is_some_and(|physics| physics.is_playing());
+
+    let mut send = |mutation| {
+        outgoing.write(OutgoingRoomMessage(RoomMessage::Mutation {
+            client: ids.client,
+            mutation: Box::new(mutation),
+        }));
+    };
+
+    for (entity, object, synced) in &mut objects {
+        let Some(description) = scene.object(entity) else {
+            continue;
+        };
+
+        let Some(mut synced) = synced else {
+            // Described objects were spawned by everyone already, and so were the ones added by
+            // others before this client joined.
+            if object.id >> 32 == u64::from(ids.client) + 1 {
+                send(SceneMutation::Spawn {
+                    object: object.id,
+                    description: description.clone(),
+                });
+            }
+            commands
+                .entity(entity)
+                .insert(SyncedState::new(&description));
+            continue;
+        };
+
+        // Where dynamic bodies fall is left to the physics of each client until it is paused, when
+        // they are shared like any other move.
+        let moved = synced.transform != description.transform
+            && !(playing && description.body == Some(RigidBody::Dynamic));
+
+        if moved || synced.material != description.material || synced.body != description.body {
+            synced.last_sent = Some(time.elapsed());
+        }
+        if moved {
+            synced.transform = description.transform;
+            send(SceneMutation::Transform {
+                object: object.id,
+                transform: description.transform,
+            });
+        }
+        if synced.material != description.material {
+            synced.material = description.material.clone();
+            send(SceneMutation::Material {
+                object: object.id,
+                material: description.material,
+            });
+        }
+        if synced.body != description.body {
+            synced.body = description.body;
+            send(SceneMutation::Body {
+                object: object.id,
+                body: description.body,
+            });
+        }
+    }
+}
+
+/// Shares deleted objects, but not the ones removed while their scene is spawned again.
+fn broadcast_despawn(
+    trigger: Trigger<OnRemove, SceneObject>,
+    objects: Query<(&SceneObject, &ChildOf), With<SyncedState>>,
+    spawned_scenes: Query<(), With<DescribedSceneSpawned>>,
+    ids: Res<ObjectIds>,
+    mut outgoing: EventWriter<OutgoingRoomMessage>,
+) {
+    let Ok((object, child_of)) = objects.get(trigger.target()) else {
+        return;
+    };
+
+    if spawned_scenes.contains(child_of.parent()) {
+        outgoing.write(OutgoingRoomMessage(RoomMessage::Mutation {
+            client: ids.client,
+            mutation: Box::new(SceneMutation::Despawn { object: object.id }),
+        }));
+    }
+}
+
+//
+// CLIENT-SIDE (wasm32) IMPLEMENTATION
+//
+#[cfg(target_arch = "wasm32")]
+mod socket {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use bevy::prelude::*;
+    use leptos::prelude::window;
+    use web_sys::js_sys::encode_uri_component;
+    use web_sys::wasm_bindgen::closure::Closure;
+    use web_sys::wasm_bindgen::JsCast;
+    use web_sys::{MessageEvent, WebSocket};
+
+    use super::{IncomingRoomMessage, OutgoingRoomMessage, RoomMessage};
+
+    /// The WebSocket to the room relay and the messages it received since the last frame.
+    pub(super) struct RoomSocket {
+        socket: Option<WebSocket>,
+        inbox: Rc<RefCell<VecDeque<String>>>,
+        /// Messages written before the socket was open.
+        outbox: VecDeque<String>,
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+    }
+
+    impl RoomSocket {
+        pub(super) fn connect(room: &str) -> Self {
+            let location = window().location();
+            let protocol = match location.protocol().as_deref() {
+                Ok("https:") => "wss",
+                _ => "ws",
+            };
+            let host = location.host().unwrap_or_default();
+            let room = String::from(encode_uri_component(room));
+            let url = format!("{protocol}://{host}/ws/{room}");
+
+            let inbox = Rc::new(RefCell::new(VecDeque::new()));
+            let on_message = Closure::<dyn FnMut(MessageEvent)>::new({
+                let inbox = inbox.clone();
+                move |event: MessageEvent| {
+                    if let Some(text) = event.data().as_string() {
+                        inbox.borrow_mut().push_back(text);
+                    }
+                }
+            });
+
+            let socket = match WebSocket::new(&url) {
+                Ok(socket) => {
+                    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+                    Some(socket)
+                }
+                Err(err) => {
+                    error!("Could not connect to room {room}: {err:?}");
+                    None
+                }
+            };
+
+            Self {
+                socket,
+                inbox,
+                outbox: VecDeque::new(),
+                _on_message: on_message,
+            }
+        }
+    }
+
+    impl Drop for RoomSocket {
+        fn drop(&mut self) {
+            if let Some(socket) = &self.socket {
+                socket.set_onmessage(None);
+                socket.close().ok();
+            }
+        }
+    }
+
+    pub(super) fn receive_room_messages(
+        socket: NonSend<RoomSocket>,
+        mut incoming: EventWriter<IncomingRoomMessage>,
+    ) {
+        for text in socket.inbox.borrow_mut().drain(..) {
+            match serde_json::from_str::<RoomMessage>(&text) {
+                Ok(message) => {
+                    incoming.write(IncomingRoomMessage(message));
+                }
+                Err(err) => warn!("Ignoring invalid room message: {err}"),
+            }
+        }
+    }
+
+    pub(super) fn send_room_messages(
+        mut socket: NonSendMut<RoomSocket>,
+        mut outgoing: EventReader<OutgoingRoomMessage>,
+    ) {
+        for OutgoingRoomMessage(message) in outgoing.read() {
+            match serde_json::to_string(message) {
+                Ok(text) => socket.outbox.push_back(text),
+                Err(err) => warn!("Could not encode room message: {err}"),
+            }
+        }
+
+        let RoomSocket { socket, outbox, .. } = &mut *socket;
+        let Some(socket) = socket.as_ref() else {
+            outbox.clear();
+            return;
+        };
+
+        if socket.ready_state() == WebSocket::OPEN {
+            for text in outbox.drain(..) {
+                socket.send_with_str(&text).ok();
+            }
+        }
+    }
+}
+
+/// -------- Room Relay --------
+#[cfg(feature = "ssr")]
+pub mod relay {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+    use axum::extract::{Path, State};
+    use axum::response::Response;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+    use super::RoomMessage;
+    use crate::scene_description::SceneDescription;
+    use crate::simulation;
+
+    #[derive(Default)]
+    struct Room {
+        /// Clients in the order they joined, with the channel to their socket.
+        members: Vec<(u32, UnboundedSender<String>)>,
+        /// The member that simulates the scene, if the room has one.
+        simulation: Option<u32>,
+    }
+
+    /// Relays `RoomMessage`s between the clients of each room.
+    ///
+    /// The relay doesn't keep the scene itself; a client that joins gets it from the member that
+    /// has been in the room the longest. In simulated rooms that is a headless Bevy app on the
+    /// server, which receives every edit first and passes on only the ones it accepts.
+    #[derive(Clone, Default)]
+    pub struct Rooms(Arc<Mutex<RoomsState>>);
+
+    #[derive(Default)]
+    struct RoomsState {
+        next_client: u32,
+        rooms: HashMap<String, Room>,
+        /// The scene new rooms are simulated with, if they are.
+        simulated_scene: Option<SceneDescription>,
+    }
+
+    /// Upgrades `/ws/{room}` requests to a WebSocket in that room.
+    pub async fn connect(
+        upgrade: WebSocketUpgrade,
+        Path(room): Path<String>,
+        State(rooms): State<Rooms>,
+    ) -> Response {
+        upgrade.on_upgrade(move |socket| rooms.serve(room, socket))
+    }
+
+    impl Rooms {
+        /// Rooms that each get a server simulation of `scene` when their first client joins.
+        pub fn simulated(scene: SceneDescription) -> Self {
+            Self(Arc::new(Mutex::new(RoomsState {
+                simulated_scene: Some(scene),
+                ..Default::default()
+            })))
+        }
+
+        async fn serve(self, room: String, mut socket: WebSocket) {
+            let (sender, mut receiver) = unbounded_channel();
+            let client = self.join(&room, sender);
+
+            loop {
+                tokio::select! {
+                    outgoing = receiver.recv() => {
+                        let Some(text) = outgoing else { break };
+                        if socket.send(Message::Text(text.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    incoming = socket.recv() => match incoming {
+                        Some(Ok(Message::Text(text))) => self.relay(&room, client, &text),
+                        Some(Ok(_)) => {}
+                        Some(Err(_)) | None => break,
+                    },
+                }
+            }
+
+            self.leave(&room, client);
+        }
+
+        fn join(&self, room: &str, sender: UnboundedSender<String>) -> u32 {
+            let mut state = self.0.lock().unwrap();
+            if !state.rooms.contains_key(room) {
+                let mut new_room = Room::default();
+                if let Some(scene) = state.simulated_scene.clone() {
+                    let client = state.next_client();
+                    let sender = simulation::server::start(self.clone(), room, client, scene);
+                    new_room.members.push((client, sender));
+                    new_room.simulation = Some(client);
+                }
+                state.rooms.insert(room.to_string(), new_room);
+            }
+
+            let client = state.next_client();
+            let members = &mut state.rooms.get_mut(room).unwrap().members;
+            if let Some((_, host)) = members.first() {
+                host.send(encode(&RoomMessage::Joined { client })).ok();
+            }
+            sender.send(encode(&RoomMessage::Welcome { client })).ok();
+            members.push((client, sender));
+
+            client
+        }
+
+        /// Closes the room once only its simulation is left, which ends the simulation too.
+        fn leave(&self, room: &str, client: u32) {
+            let mut state = self.0.lock().unwrap();
+            if let Some(Room {
+                members,
+                simulation,
+            }) = state.rooms.get_mut(room)
+            {
+                members.retain(|(member, _)| *member != client);
+                if members
+                    .iter()
+                    .all(|(member, _)| Some(*member) == *simulation)
+                {
+                    state.rooms.remove(room);
+                }
+            }
+        }
+
+        /// Sends scenes only to the client they are meant for and mutations to everyone else.
+        ///
+        /// Scenes are only taken from the member that got told about the joining client, which is
+        /// the simulation in simulated rooms and the longest connected client otherwise. In
+        /// simulated rooms, mutations of clients go to the simulation only. The simulation passes
+        /// on the ones it accepts with their author, and streams snapshots to everyone.
+        pub(crate) fn relay(&self, room: &str, sender: u32, text: &str) {
+            let Ok(message) = serde_json::from_str::<RoomMessage>(text) else {
+                return;
+            };
+
+            let state = self.0.lock().unwrap();
+            let Some(Room {
+                members,
+                simulation,
+            }) = state.rooms.get(room)
+            else {
+                return;
+            };
+            let from_simulation = *simulation == Some(sender);
+            let from_host = match simulation {
+                Some(_) => from_simulation,
+                None => members.first().is_some_and(|(host, _)| *host == sender),
+            };
+
+            match message {
+                RoomMessage::Sync { client, .. } if from_host => {
+                    for (_, member) in members.iter().filter(|(member, _)| *member == client) {
+                        member.send(text.to_string()).ok();
+                    }
+                }
+                RoomMessage::Mutation { client, mutation } => {
+                    let author = if from_simulation { client } else { sender };
+                    let text = encode(&RoomMessage::Mutation {
+                        client: author,
+                        mutation,
+                    });
+                    let recipients = members.iter().filter(|(member, _)| match simulation {
+                        Some(simulation) if !from_simulation => member == simulation,
+                        _ => *member != sender && *member != author,
+                    });
+                    for (_, member) in recipients {
+                        member.send(text.clone()).ok();
+                    }
+                }
+                RoomMessage::Snapshot { .. } if from_simulation => {
+                    for (_, member) in members.iter().filter(|(member, _)| *member != sender) {
+                        member.send(text.to_string()).ok();
+                    }
+                }
+                RoomMessage::Welcome { .. }
+                | RoomMessage::Joined { .. }
+                | RoomMessage::Sync { .. }
+                | RoomMessage::Snapshot { .. } => {}
+            }
+        }
+    }
+
+    impl RoomsState {
+        fn next_client(&mut self) -> u32 {
+            let client = self.next_client;
+            self.next_client = self.next_client.wrapping_add(1);
+            client
+        }
+    }
+
+    fn encode(message: &RoomMessage) -> String {
+        serde_json::to_string(message).expect("room messages always serialize")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bevy::ecs::system::RunSystemOnce;
+        use bevy::prelude::*;
+        use bevy::time::TimeUpdateStrategy;
+        use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+        use super::{encode, Rooms};
+        use crate::collaboration::{
+            CollaborationPlugin, IncomingRoomMessage, OutgoingRoomMessage, RoomMessage,
+            BROADCAST_INTERVAL,
+        };
+        use crate::physics::{PhysicsCommand, PhysicsPlugin, PhysicsSimulation};
+        use crate::scene_description::{
+            spawn_object, DescribedScene, MaterialDescription, MeshDescription, ObjectAssets,
+            ObjectDescription, ObjectIds, SceneDescription, SceneObject, TransformDescription,
+        };
+        use crate::simulation::headless_app;
+
+        const ROOM: &str = "test";
+        const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+        /// A headless app connected to the room like a browser would be.
+        struct Client {
+            id: u32,
+            app: App,
+            receiver: UnboundedReceiver<String>,
+        }
+
+        impl Client {
+            fn join(rooms: &Rooms) -> Self {
+                let (sender, receiver) = unbounded_channel();
+                let id = rooms.join(ROOM, sender);
+                let scene: SceneDescription = ron::from_str(DEFAULT_SCENE).unwrap();
+                let mut app = headless_app(scene);
+                app.add_plugins((
+                    CollaborationPlugin {
+                        room: ROOM.to_string(),
+                    },
+                    PhysicsPlugin,
+                ))
+                .insert_resource(TimeUpdateStrategy::ManualDuration(BROADCAST_INTERVAL));
+                app.finish();
+                app.cleanup();
+
+                Self { id, app, receiver }
+            }
+
+            /// The entity of the object with this id, which has to exist exactly once.
+            fn object(&mut self, id: u64) -> Entity {
+                let objects: Vec<Entity> = self
+                    .app
+                    .world_mut()
+                    .query::<(Entity, &SceneObject)>()
+                    .iter(self.app.world())
+                    .filter(|(_, object)| object.id == id)
+                    .map(|(entity, _)| entity)
+                    .collect();
+                assert_eq!(objects.len(), 1, "object {id} should exist once");
+                objects[0]
+            }
+        }
+
+        /// Lets every client receive what was relayed to it, update by one broadcast interval and
+        /// send what it broadcasts, a few times over so that changes settle.
+        fn exchange(rooms: &Rooms, clients: &mut [&mut Client]) {
+            for _ in 0..4 {
+                for client in clients.iter_mut() {
+                    while let Ok(text) = client.receiver.try_recv() {
+                        let message = serde_json::from_str(&text).unwrap();
+                        client
+                            .app
+                            .world_mut()
+                            .send_event(IncomingRoomMessage(message));
+                    }
+                    client.app.update();
+                    let outgoing: Vec<_> = client
+                        .app
+                        .world_mut()
+                        .resource_mut::<Events<OutgoingRoomMessage>>()
+                        .drain()
+                        .collect();
+                    for OutgoingRoomMessage(message) in outgoing {
+                        rooms.relay(ROOM, client.id, &encode(&message));
+                    }
+                }
+            }
+        }
+
+        /// Everything relayed to `receiver` so far.
+        fn received(receiver: &mut UnboundedReceiver<String>) -> Vec<RoomMessage> {
+            std::iter::from_fn(|| receiver.try_recv().ok())
+                .map(|text| serde_json::from_str(&text).unwrap())
+                .collect()
+        }
+
+        #[test]
+        fn scenes_are_only_taken_from_the_host() {
+            let scene: SceneDescription = ron::from_str(DEFAULT_SCENE).unwrap();
+            let forged = |client| RoomMessage::Sync {
+                client,
+                scene: SceneDescription {
+                    objects: Vec::new(),
+                    ..scene.clone()
+                },
+                objects: Vec::new(),
+            };
+
+            let rooms = Rooms::default();
+            let mut receivers: Vec<_> = (0..3)
+                .map(|_| {
+                    let (sender, receiver) = unbounded_channel();
+                    rooms.join(ROOM, sender);
+                    receiver
+                })
+                .collect();
+            for receiver in &mut receivers {
+                received(receiver);
+            }
+
+            rooms.relay(ROOM, 1, &encode(&forged(2)));
+            assert_eq!(received(&mut receivers[2]), []);
+            rooms.relay(ROOM, 0, &encode(&forged(2)));
+            assert_eq!(received(&mut receivers[2]), [forged(2)]);
+
+            // In simulated rooms, not even the longest connected client may send scenes.
+            let rooms = Rooms::simulated(scene.clone());
+            let (host, mut host_receiver) = unbounded_channel();
+            let (guest, mut guest_receiver) = unbounded_channel();
+            let host = rooms.join(ROOM, host);
+            let guest = rooms.join(ROOM, guest);
+            received(&mut host_receiver);
+
+            rooms.relay(ROOM, host, &encode(&forged(guest)));
+            assert!(!received(&mut guest_receiver).contains(&forged(guest)));
+
+            rooms.leave(ROOM, host);
+            rooms.leave(ROOM, guest);
+        }
+
+        #[test]
+        fn everyone_plays_the_physics_together() {
+            let rooms = Rooms::default();
+            let mut host = Client::join(&rooms);
+            let mut guest = Client::join(&rooms);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+
+            let playing = |client: &Client| {
+                client
+                    .app
+                    .world()
+                    .resource::<PhysicsSimulation>()
+                    .is_playing()
+            };
+            host.app.world_mut().send_event(PhysicsCommand::Play);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+            assert!(playing(&host) && playing(&guest));
+
+            guest.app.world_mut().send_event(PhysicsCommand::Pause);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+            assert!(!playing(&host) && !playing(&guest));
+
+            let steps =
+                |client: &Client| client.app.world().resource::<PhysicsSimulation>().steps();
+            guest.app.world_mut().send_event(PhysicsCommand::Reset);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+            assert_eq!((steps(&host), steps(&guest)), (0, 0));
+            host.app.world_mut().send_event(PhysicsCommand::Step);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+            assert_eq!((steps(&host), steps(&guest)), (1, 1));
+        }
+
+        #[test]
+        fn late_joiners_share_objects_added_before_they_joined() {
+            let rooms = Rooms::default();
+            let mut host = Client::join(&rooms);
+            exchange(&rooms, &mut [&mut host]);
+
+            let added = host
+                .app
+                .world_mut()
+                .run_system_once(
+                    |mut commands: Commands,
+                     mut ids: ResMut<ObjectIds>,
+                     mut assets: ObjectAssets,
+                     scene: Single<Entity, With<DescribedScene>>| {
+                        let id = ids.next_id();
+                        let description = ObjectDescription {
+                            name: Some("Ball".to_string()),
+                            mesh: MeshDescription::Sphere { radius: 0.5 },
+                            material: MaterialDescription::default(),
+                            transform: TransformDescription::default(),
+                            roles: Vec::new(),
+                            body: None,
+                        };
+                        spawn_object(&mut commands, *scene, id, &description, &mut assets);
+                        id
+                    },
+                )
+                .unwrap();
+            exchange(&rooms, &mut [&mut host]);
+
+            let mut guest = Client::join(&rooms);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+            host.object(added);
+            guest.object(added);
+
+            let ball = host.object(added);
+            host.app
+                .world_mut()
+                .get_mut::<Transform>(ball)
+                .unwrap()
+                .translation = Vec3::new(1.0, 2.0, 3.0);
+            exchange(&rooms, &mut [&mut host, &mut guest]);
+
+            let ball = guest.object(added);
+            assert_eq!(
+                guest
+                    .app
+                    .world()
+                    .get::<Transform>(ball)
+                    .unwrap()
+                    .translation,
+                Vec3::new(1.0, 2.0, 3.0)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::{
+        CollaborationPlugin, OutgoingRoomMessage, RoomMessage, SceneMutation, BROADCAST_INTERVAL,
+    };
+    use crate::physics::{PhysicsCommand, PhysicsPlugin, Playback};
+    use crate::scene_description::{RigidBody, SceneDescription, SceneObject};
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    /// Updates `app` by one broadcast interval, and returns the mutations it sent.
+    fn broadcast(app: &mut App) -> Vec<SceneMutation> {
+        app.update();
+        app.world_mut()
+            .resource_mut::<Events<OutgoingRoomMessage>>()
+            .drain()
+            .filter_map(|OutgoingRoomMessage(message)| match message {
+                RoomMessage::Mutation { mutation, .. } => Some(*mutation),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn falling_bodies_are_shared_once_the_physics_pauses() {
+        let mut scene: SceneDescription = ron::from_str(DEFAULT_SCENE).unwrap();
+        scene.objects[0].transform.translation[1] = 3.0;
+        let mut app = headless_app(scene);
+        app.add_plugins((
+            CollaborationPlugin {
+                room: "physics".to_string(),
+            },
+            PhysicsPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(BROADCAST_INTERVAL));
+        app.finish();
+        app.cleanup();
+        app.update();
+        broadcast(&mut app);
+
+        let cube = app
+            .world_mut()
+            .query::<(Entity, &SceneObject)>()
+            .iter(app.world())
+            .find(|(_, object)| object.id == 0)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.world_mut().get_mut::<SceneObject>(cube).unwrap().body = Some(RigidBody::Dynamic);
+        assert_eq!(
+            broadcast(&mut app),
+            [SceneMutation::Body {
+                object: 0,
+                body: Some(RigidBody::Dynamic),
+            }]
+        );
+
+        // Everyone plays the physics, but where the bodies fall meanwhile isn't sent.
+        app.world_mut().send_event(PhysicsCommand::Play);
+        assert_eq!(
+            broadcast(&mut app),
+            [SceneMutation::Physics(Playback::Play)]
+        );
+        for _ in 0..3 {
+            app.world_mut().send_event(PhysicsCommand::Step);
+            assert_eq!(
+                broadcast(&mut app),
+                [SceneMutation::Physics(Playback::Step)]
+            );
+        }
+
+        app.world_mut().send_event(PhysicsCommand::Pause);
+        let mutations = broadcast(&mut app);
+        let fallen = app.world().get::<Transform>(cube).unwrap().translation;
+        assert!(fallen.y < 3.0);
+        assert_eq!(
+            mutations,
+            [
+                SceneMutation::Physics(Playback::Pause),
+                SceneMutation::Transform {
+                    object: 0,
+                    transform: (&Transform::from_translation(fallen)).into(),
+                }
+            ]
+        );
+    }
+}
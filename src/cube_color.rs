@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use crate::history::{EditCommand, SceneEdit};
+
+/// Base color of the cube, kept in sync with a Leptos signal.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct CubeColor(pub Color);
+
+impl Default for CubeColor {
+    fn default() -> Self {
+        Self(Color::srgb(0.3, 0.6, 0.9))
+    }
+}
+
+impl CubeColor {
+    /// Parses a `#rrggbb` string as produced by `<input type="color">`.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        Srgba::hex(hex).ok().map(|color| Self(color.into()))
+    }
+
+    /// Formats the color as `#rrggbb` so it can be fed back into `<input type="color">`.
+    pub fn to_hex(&self) -> String {
+        Srgba::from(self.0).with_alpha(1.0).to_hex().to_lowercase()
+    }
+}
+
+/// Marks the entities whose material follows `CubeColor`.
+#[derive(Component)]
+pub struct Cube;
+
+/// Applies `CubeColor` to the material of every `Cube`, as an `EditCommand`.
+///
+/// The resource itself is inserted by `sync_leptos_signal_with_resource`.
+pub struct CubeColorPlugin;
+
+impl Plugin for CubeColorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_cube_color.run_if(resource_exists_and_changed::<CubeColor>),
+        );
+    }
+}
+
+fn apply_cube_color(
+    color: Res<CubeColor>,
+    cubes: Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<Cube>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    for (entity, material) in &cubes {
+        let Some(material) = materials.get(material) else {
+            continue;
+        };
+        if material.base_color != color.0 {
+            edits.write(EditCommand {
+                entity,
+                edit: SceneEdit::Material(Box::new(StandardMaterial {
+                    base_color: color.0,
+                    ..material.clone()
+                })),
+            });
+        }
+    }
+}
@@ -0,0 +1,570 @@
+use std::mem;
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::cube_color::{Cube, CubeColor};
+use crate::keyboard_focus::canvas_focused;
+use crate::scene_description::{
+    describe_light, insert_light, insert_object, DescribedSceneSpawned, EnvironmentDescription,
+    LightDescription, MaterialDescription, ObjectAssets, ObjectDescription, ObjectIds, RigidBody,
+    SceneEnvironment, SceneObject,
+};
+
+/// Edits of the same entity closer together than this are undone as one, like the frames of a
+/// drag or the keystrokes of a word.
+const MERGE_WINDOW: Duration = Duration::from_millis(500);
+
+/// How many edits can be undone.
+const MAX_UNDO: usize = 100;
+
+/// The new value of something in the scene.
+#[derive(Clone, Debug)]
+pub enum SceneEdit {
+    /// The `Text` of a label.
+    Text(String),
+    Transform(Transform),
+    /// The whole `StandardMaterial` of a `MeshMaterial3d`.
+    Material(Box<StandardMaterial>),
+    /// Which material a `MeshMaterial3d` uses, like one of the `MaterialLibrary`.
+    MaterialHandle(Handle<StandardMaterial>),
+    PointLight(PointLight),
+    DirectionalLight(DirectionalLight),
+    SpotLight(SpotLight),
+    /// The `SceneEnvironment` of a `DescribedScene`.
+    Environment(Box<EnvironmentDescription>),
+    /// The body of a `SceneObject` in the physics simulation.
+    Body(Option<RigidBody>),
+    /// The entity as a child of the `DescribedScene` entity in the tuple, or no entity at all.
+    ///
+    /// Editing an entity that doesn't exist into one spawns it, which may give it another
+    /// `Entity`; editing it into `None` despawns it.
+    Child(Option<(Entity, SceneChild)>),
+}
+
+impl SceneEdit {
+    /// The value, to be changed through reflection, or `None` for children that are spawned or
+    /// despawned as a whole.
+    pub fn value_mut(&mut self) -> Option<&mut dyn PartialReflect> {
+        match self {
+            SceneEdit::Text(text) => Some(text),
+            SceneEdit::Transform(transform) => Some(transform),
+            SceneEdit::Material(material) => Some(material.as_mut()),
+            SceneEdit::MaterialHandle(handle) => Some(handle),
+            SceneEdit::PointLight(point_light) => Some(point_light),
+            SceneEdit::DirectionalLight(directional_light) => Some(directional_light),
+            SceneEdit::SpotLight(spot_light) => Some(spot_light),
+            SceneEdit::Environment(environment) => Some(environment.as_mut()),
+            SceneEdit::Body(body) => Some(body),
+            SceneEdit::Child(_) => None,
+        }
+    }
+}
+
+/// A child of a scene that can be added and removed, as it is spawned.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneChild {
+    Object {
+        id: u64,
+        description: Box<ObjectDescription>,
+    },
+    Light(LightDescription),
+}
+
+/// Changes `entity` as described by `edit`, so that it can be undone.
+///
+/// Systems editing the scene on behalf of the user write these rather than changing the scene
+/// themselves, so every edit ends up in the `History`. Children are added with an `Entity` from
+/// `Commands::spawn_empty` and a `SceneEdit::Child`, and removed with `SceneEdit::Child(None)`.
+#[derive(Event, Clone, Debug)]
+pub struct EditCommand {
+    pub entity: Entity,
+    pub edit: SceneEdit,
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryCommand {
+    Undo,
+    Redo,
+}
+
+/// Whether there is anything to undo or redo, written whenever that changes.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HistoryState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+/// One step of the history.
+struct Change {
+    entity: Entity,
+    before: SceneEdit,
+    after: SceneEdit,
+    /// When the change was last extended.
+    at: Duration,
+}
+
+/// The edits applied from `EditCommand`s, to be undone and redone.
+#[derive(Resource, Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    /// Whether the next edit may continue the last one, which it can't after an undo or redo.
+    merging: bool,
+}
+
+impl History {
+    pub fn state(&self) -> HistoryState {
+        HistoryState {
+            can_undo: !self.undo.is_empty(),
+            can_redo: !self.redo.is_empty(),
+        }
+    }
+
+    fn record(&mut self, entity: Entity, before: SceneEdit, after: SceneEdit, at: Duration) {
+        self.redo.clear();
+
+        if let Some(last) = self.undo.last_mut().filter(|last| {
+            self.merging
+                && !matches!(after, SceneEdit::Child(_))
+                && last.entity == entity
+                && mem::discriminant(&last.after) == mem::discriminant(&after)
+                && at.saturating_sub(last.at) < MERGE_WINDOW
+        }) {
+            last.after = after;
+            last.at = at;
+        } else {
+            self.undo.push(Change {
+                entity,
+                before,
+                after,
+                at,
+            });
+            if self.undo.len() > MAX_UNDO {
+                self.undo.remove(0);
+            }
+        }
+
+        self.merging = true;
+    }
+
+    fn step(&mut self, command: HistoryCommand, targets: &mut EditTargets) {
+        let (from, to) = match command {
+            HistoryCommand::Undo => (&mut self.undo, &mut self.redo),
+            HistoryCommand::Redo => (&mut self.redo, &mut self.undo),
+        };
+
+        // Changes of entities that are gone since, like after loading another scene, are dropped.
+        while let Some(change) = from.pop() {
+            let value = match command {
+                HistoryCommand::Undo => change.before.clone(),
+                HistoryCommand::Redo => change.after.clone(),
+            };
+            let mut entity = change.entity;
+            if targets.swap(&mut entity, value).is_some() {
+                to.push(change);
+                // A child that was spawned again is another entity, which the other changes of
+                // the old one now apply to.
+                if entity != to[to.len() - 1].entity {
+                    let old = to[to.len() - 1].entity;
+                    for change in from.iter_mut().chain(to.iter_mut()) {
+                        if change.entity == old {
+                            change.entity = entity;
+                        }
+                    }
+                }
+                break;
+            }
+        }
+
+        self.merging = false;
+    }
+
+    /// Drops the children of `scene` that were added or removed, which mustn't come back into
+    /// whatever the scene holds once it is spawned again.
+    fn forget_children_of(&mut self, scene: Entity) {
+        let of_scene = |edit: &SceneEdit| matches!(edit, SceneEdit::Child(Some((parent, _))) if *parent == scene);
+        for changes in [&mut self.undo, &mut self.redo] {
+            changes.retain(|change| !of_scene(&change.before) && !of_scene(&change.after));
+        }
+    }
+}
+
+/// Applies `EditCommand`s and keeps their `History`.
+///
+/// Ctrl+Z undoes and Ctrl+Shift+Z redoes while the canvas has focus; pages can send
+/// `HistoryCommand`s from Leptos as well and show `HistoryState`.
+pub struct HistoryPlugin;
+
+/// The systems applying `EditCommand`s in `PostUpdate`, for ordering the ones that write them.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HistorySystems;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<History>()
+            .add_observer(forget_respawned_children)
+            .add_event::<EditCommand>()
+            .add_event::<HistoryCommand>()
+            .add_event::<HistoryState>()
+            .add_systems(Update, undo_redo_shortcuts.run_if(canvas_focused))
+            .add_systems(
+                PostUpdate,
+                (apply_edit_commands, undo_or_redo, report_history_state)
+                    .chain()
+                    .in_set(HistorySystems)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+type EditedParts = (
+    Option<&'static mut Text>,
+    Option<&'static mut Transform>,
+    Option<&'static mut MeshMaterial3d<StandardMaterial>>,
+    Option<&'static mut PointLight>,
+    Option<&'static mut DirectionalLight>,
+    Option<&'static mut SpotLight>,
+    Option<&'static mut SceneEnvironment>,
+    Option<&'static mut SceneObject>,
+    Has<Cube>,
+    Option<&'static ChildOf>,
+    Option<&'static Name>,
+);
+
+#[derive(SystemParam)]
+struct EditTargets<'w, 's> {
+    commands: Commands<'w, 's>,
+    entities: Query<'w, 's, EditedParts>,
+    assets: ObjectAssets<'w>,
+    ids: Option<ResMut<'w, ObjectIds>>,
+    cube_color: Option<ResMut<'w, CubeColor>>,
+}
+
+impl EditTargets<'_, '_> {
+    /// Applies `edit` to `entity` and returns the value it replaced, or `None` if the entity has
+    /// nothing to apply it to. Children that are spawned again change `entity`.
+    fn swap(&mut self, entity: &mut Entity, edit: SceneEdit) -> Option<SceneEdit> {
+        if let SceneEdit::Child(new) = edit {
+            return self.swap_child(entity, new);
+        }
+        let entity = *entity;
+
+        let (
+            text,
+            transform,
+            material,
+            point_light,
+            directional_light,
+            spot_light,
+            environment,
+            object,
+            is_cube,
+            ..,
+        ) = self.entities.get_mut(entity).ok()?;
+
+        match edit {
+            SceneEdit::Text(new) => Some(SceneEdit::Text(mem::replace(&mut text?.0, new))),
+            SceneEdit::Transform(new) => Some(SceneEdit::Transform(mem::replace(
+                transform?.into_inner(),
+                new,
+            ))),
+            SceneEdit::Material(new) => {
+                let material = self.assets.materials.get_mut(&material?.0)?;
+                // Keeps the color picker in step; the cube already has the color, so setting it
+                // doesn't lead to another edit.
+                if let (true, Some(cube_color)) = (is_cube, self.cube_color.as_mut()) {
+                    cube_color.set_if_neq(CubeColor(new.base_color));
+                }
+                Some(SceneEdit::Material(Box::new(mem::replace(material, *new))))
+            }
+            SceneEdit::MaterialHandle(new) => {
+                let material = material?;
+                let base_color = self.assets.materials.get(&new)?.base_color;
+                if let (true, Some(cube_color)) = (is_cube, self.cube_color.as_mut()) {
+                    cube_color.set_if_neq(CubeColor(base_color));
+                }
+                Some(SceneEdit::MaterialHandle(mem::replace(
+                    &mut material.into_inner().0,
+                    new,
+                )))
+            }
+            SceneEdit::PointLight(new) => Some(SceneEdit::PointLight(mem::replace(
+                point_light?.into_inner(),
+                new,
+            ))),
+            SceneEdit::DirectionalLight(new) => Some(SceneEdit::DirectionalLight(mem::replace(
+                directional_light?.into_inner(),
+                new,
+            ))),
+            SceneEdit::SpotLight(new) => Some(SceneEdit::SpotLight(mem::replace(
+                spot_light?.into_inner(),
+                new,
+            ))),
+            SceneEdit::Environment(new) => Some(SceneEdit::Environment(Box::new(mem::replace(
+                &mut environment?.into_inner().0,
+                *new,
+            )))),
+            SceneEdit::Body(new) => Some(SceneEdit::Body(mem::replace(
+                &mut object?.into_inner().body,
+                new,
+            ))),
+            SceneEdit::Child(_) => unreachable!("children are swapped as a whole"),
+        }
+    }
+
+    /// Despawns the child `entity` or spawns it as described, whichever it isn't yet.
+    fn swap_child(
+        &mut self,
+        entity: &mut Entity,
+        new: Option<(Entity, SceneChild)>,
+    ) -> Option<SceneEdit> {
+        match (self.child(*entity), new) {
+            (Some(current), None) => {
+                self.commands.entity(*entity).despawn();
+                Some(SceneEdit::Child(Some(current)))
+            }
+            (None, Some((scene, child))) => {
+                self.commands.get_entity(scene).ok()?;
+                // Entities reserved for new children are still there, despawned ones are not.
+                let target = match self.commands.get_entity(*entity) {
+                    Ok(target) => target.id(),
+                    Err(_) => self.commands.spawn_empty().id(),
+                };
+                *entity = target;
+
+                match child {
+                    SceneChild::Object {
+                        mut id,
+                        description,
+                    } => {
+                        // Objects of others that come back are this client's to share again.
+                        if let Some(ids) = self.ids.as_mut() {
+                            if id >> 32 != u64::from(ids.client) + 1 {
+                                id = ids.next_id();
+                            }
+                        }
+                        insert_object(
+                            self.commands.entity(target),
+                            scene,
+                            id,
+                            &description,
+                            &mut self.assets,
+                        );
+                    }
+                    SceneChild::Light(light) => {
+                        insert_light(self.commands.entity(target), scene, &light);
+                    }
+                }
+                Some(SceneEdit::Child(None))
+            }
+            _ => None,
+        }
+    }
+
+    /// The scene and description of the object or light `entity`.
+    fn child(&self, entity: Entity) -> Option<(Entity, SceneChild)> {
+        let (_, transform, material, point, directional, spot, _, object, _, child_of, name) =
+            self.entities.get(entity).ok()?;
+        let (ChildOf(scene), transform) = (child_of?, transform?);
+
+        let child = if let (Some(object), Some(material)) = (object, material) {
+            SceneChild::Object {
+                id: object.id,
+                description: Box::new(ObjectDescription {
+                    name: name.map(|name| name.to_string()),
+                    mesh: object.mesh,
+                    material: self
+                        .assets
+                        .materials
+                        .get(&material.0)
+                        .map(|current| MaterialDescription {
+                            name: self.assets.library.name_of(&material.0).map(str::to_string),
+                            ..MaterialDescription::from(current)
+                        })
+                        .unwrap_or_default(),
+                    transform: transform.into(),
+                    roles: object.roles.clone(),
+                    body: object.body,
+                }),
+            }
+        } else if point.is_some() || directional.is_some() || spot.is_some() {
+            SceneChild::Light(describe_light(transform, (point, directional, spot)))
+        } else {
+            return None;
+        };
+
+        Some((*scene, child))
+    }
+}
+
+fn apply_edit_commands(
+    mut commands: EventReader<EditCommand>,
+    mut targets: EditTargets,
+    mut history: ResMut<History>,
+    time: Res<Time>,
+) {
+    for EditCommand { mut entity, edit } in commands.read().cloned() {
+        if let Some(before) = targets.swap(&mut entity, edit.clone()) {
+            history.record(entity, before, edit, time.elapsed());
+        }
+    }
+}
+
+fn forget_respawned_children(
+    trigger: Trigger<OnRemove, DescribedSceneSpawned>,
+    mut history: ResMut<History>,
+) {
+    history.forget_children_of(trigger.target());
+}
+
+fn undo_or_redo(
+    mut commands: EventReader<HistoryCommand>,
+    mut targets: EditTargets,
+    mut history: ResMut<History>,
+) {
+    for command in commands.read() {
+        history.step(*command, &mut targets);
+    }
+}
+
+fn report_history_state(
+    history: Res<History>,
+    mut last: Local<Option<HistoryState>>,
+    mut states: EventWriter<HistoryState>,
+) {
+    let state = history.state();
+
+    if *last != Some(state) {
+        *last = Some(state);
+        states.write(state);
+    }
+}
+
+fn undo_redo_shortcuts(keys: Res<ButtonInput<KeyCode>>, mut commands: EventWriter<HistoryCommand>) {
+    let control = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+
+    if control && keys.just_pressed(KeyCode::KeyZ) {
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            commands.write(HistoryCommand::Redo);
+        } else {
+            commands.write(HistoryCommand::Undo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{EditCommand, HistoryCommand, HistoryPlugin, SceneEdit};
+    use crate::cube_color::Cube;
+    use crate::scene_description::{SceneCommand, SceneDescription};
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    /// Where the cube is, if there is one, and how many point lights there are.
+    fn children(app: &mut App) -> (Option<Vec3>, usize) {
+        let world = app.world_mut();
+        let cube = world
+            .query_filtered::<&Transform, With<Cube>>()
+            .iter(world)
+            .next()
+            .map(|transform| transform.translation);
+        let lights = world.query::<&PointLight>().iter(world).count();
+        (cube, lights)
+    }
+
+    fn step(app: &mut App, command: HistoryCommand) {
+        app.world_mut().send_event(command);
+        app.update();
+    }
+
+    #[test]
+    fn removed_children_come_back_with_their_edits() {
+        let scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        let mut app = headless_app(scene);
+        app.add_plugins(HistoryPlugin);
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let world = app.world_mut();
+        let cube = world
+            .query_filtered::<Entity, With<Cube>>()
+            .single(world)
+            .unwrap();
+        let light = world
+            .query_filtered::<Entity, With<PointLight>>()
+            .single(world)
+            .unwrap();
+        let (start, moved) = (Vec3::new(0.0, 0.5, 0.0), Vec3::new(2.0, 0.5, 0.0));
+        app.world_mut().send_event(EditCommand {
+            entity: cube,
+            edit: SceneEdit::Transform(Transform::from_translation(moved)),
+        });
+        app.update();
+        app.world_mut().send_event_batch([
+            EditCommand {
+                entity: cube,
+                edit: SceneEdit::Child(None),
+            },
+            EditCommand {
+                entity: light,
+                edit: SceneEdit::Child(None),
+            },
+        ]);
+        app.update();
+        assert_eq!(children(&mut app), (None, 0));
+
+        step(&mut app, HistoryCommand::Undo);
+        assert_eq!(children(&mut app), (None, 1));
+        step(&mut app, HistoryCommand::Undo);
+        assert_eq!(children(&mut app), (Some(moved), 1));
+        // The move applies to the cube that came back.
+        step(&mut app, HistoryCommand::Undo);
+        assert_eq!(children(&mut app), (Some(start), 1));
+
+        step(&mut app, HistoryCommand::Redo);
+        assert_eq!(children(&mut app), (Some(moved), 1));
+        step(&mut app, HistoryCommand::Redo);
+        step(&mut app, HistoryCommand::Redo);
+        assert_eq!(children(&mut app), (None, 0));
+    }
+
+    #[test]
+    fn removed_children_stay_out_of_a_loaded_scene() {
+        let scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        let mut app = headless_app(scene.clone());
+        app.add_plugins(HistoryPlugin);
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let world = app.world_mut();
+        let light = world
+            .query_filtered::<Entity, With<PointLight>>()
+            .single(world)
+            .unwrap();
+        app.world_mut().send_event(EditCommand {
+            entity: light,
+            edit: SceneEdit::Child(None),
+        });
+        app.update();
+        app.world_mut().send_event(SceneCommand::Load(scene));
+        app.update();
+        app.update();
+        assert_eq!(children(&mut app).1, 1);
+
+        step(&mut app, HistoryCommand::Undo);
+        assert_eq!(children(&mut app).1, 1);
+    }
+}
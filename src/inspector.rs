@@ -0,0 +1,479 @@
+use bevy::diagnostic::FrameCount;
+use bevy::prelude::*;
+use bevy::reflect::{ReflectPath, ReflectRef};
+
+use crate::history::{EditCommand, SceneEdit};
+
+/// Snapshots are taken on every this many frames.
+const SNAPSHOT_FRAMES: u32 = 10;
+
+/// Fields of `StandardMaterial` shown for `MeshMaterial3d`.
+const MATERIAL_FIELDS: [&str; 7] = [
+    "base_color",
+    "emissive",
+    "metallic",
+    "perceptual_roughness",
+    "reflectance",
+    "unlit",
+    "double_sided",
+];
+
+/// The value of one field, in the form it is edited in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Number(f32),
+    Toggle(bool),
+    /// A rotation as Euler XYZ angles in degrees.
+    Rotation([f32; 3]),
+    /// An sRGB color with alpha.
+    Color([f32; 4]),
+    /// Anything else, shown but not editable.
+    Text(String),
+}
+
+/// A field and its reflection path within its component, like `.translation.x`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InspectorField {
+    pub path: String,
+    pub value: FieldValue,
+}
+
+/// The components the inspector shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    Transform,
+    Mesh,
+    /// The `StandardMaterial` asset of `MeshMaterial3d`.
+    Material,
+    PointLight,
+}
+
+impl ComponentKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ComponentKind::Transform => "Transform",
+            ComponentKind::Mesh => "Mesh3d",
+            ComponentKind::Material => "MeshMaterial3d",
+            ComponentKind::PointLight => "PointLight",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InspectorComponent {
+    pub kind: ComponentKind,
+    pub fields: Vec<InspectorField>,
+}
+
+/// An entity of the tree, in depth-first order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InspectorEntity {
+    pub entity: Entity,
+    pub label: String,
+    /// How many ancestors the entity has.
+    pub depth: usize,
+    pub components: Vec<InspectorComponent>,
+}
+
+/// All entities with a `Transform`, as a tree, with the inspected components.
+#[derive(Event, Clone, Debug, Default, PartialEq)]
+pub struct InspectorSnapshot(pub Vec<InspectorEntity>);
+
+impl InspectorSnapshot {
+    pub fn entity(&self, entity: Entity) -> Option<&InspectorEntity> {
+        self.0.iter().find(|inspected| inspected.entity == entity)
+    }
+
+    pub fn field(&self, entity: Entity, kind: ComponentKind, path: &str) -> Option<&FieldValue> {
+        self.entity(entity)?
+            .components
+            .iter()
+            .find(|component| component.kind == kind)?
+            .fields
+            .iter()
+            .find(|field| field.path == path)
+            .map(|field| &field.value)
+    }
+}
+
+/// Sets a field of a component from the inspector.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct InspectorEdit {
+    pub entity: Entity,
+    pub component: ComponentKind,
+    pub path: String,
+    pub value: FieldValue,
+}
+
+/// Sends `InspectorSnapshot`s of the world every few frames, when something changed, and
+/// applies `InspectorEdit`s.
+///
+/// Fields are read and written through reflection, so every field of the inspected components
+/// shows up without listing them here.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InspectorSnapshot>()
+            .add_event::<InspectorEdit>()
+            .add_systems(
+                Update,
+                (
+                    apply_inspector_edits,
+                    send_inspector_snapshot
+                        .run_if(|frames: Res<FrameCount>| frames.0.is_multiple_of(SNAPSHOT_FRAMES)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Adds the editable fields of `value` below `path`.
+fn collect_fields(value: &dyn PartialReflect, path: String, fields: &mut Vec<InspectorField>) {
+    let mut push = |value| {
+        fields.push(InspectorField {
+            path: path.clone(),
+            value,
+        })
+    };
+
+    if let Some(number) = value.try_downcast_ref::<f32>() {
+        push(FieldValue::Number(*number));
+    } else if let Some(toggle) = value.try_downcast_ref::<bool>() {
+        push(FieldValue::Toggle(*toggle));
+    } else if let Some(rotation) = value.try_downcast_ref::<Quat>() {
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        push(FieldValue::Rotation([x, y, z].map(f32::to_degrees)));
+    } else if let Some(color) = value.try_downcast_ref::<Color>() {
+        push(FieldValue::Color(Srgba::from(*color).to_f32_array()));
+    } else if let Some(color) = value.try_downcast_ref::<LinearRgba>() {
+        push(FieldValue::Color(Srgba::from(*color).to_f32_array()));
+    } else {
+        match value.reflect_ref() {
+            ReflectRef::Struct(value) => {
+                for index in 0..value.field_len() {
+                    if let (Some(name), Some(field)) = (value.name_at(index), value.field_at(index))
+                    {
+                        collect_fields(field, format!("{path}.{name}"), fields);
+                    }
+                }
+            }
+            ReflectRef::TupleStruct(value) => {
+                for (index, field) in value.iter_fields().enumerate() {
+                    collect_fields(field, format!("{path}.{index}"), fields);
+                }
+            }
+            _ => push(FieldValue::Text(format!("{value:?}"))),
+        }
+    }
+}
+
+fn component_fields(value: &dyn PartialReflect) -> Vec<InspectorField> {
+    let mut fields = Vec::new();
+    collect_fields(value, String::new(), &mut fields);
+    fields
+}
+
+/// Sets the field at `path` of `target` and returns whether that changed it; values of another
+/// type than the field are ignored.
+fn set_field(target: &mut dyn PartialReflect, path: &str, value: &FieldValue) -> bool {
+    let Ok(field) = path.reflect_element_mut(target) else {
+        return false;
+    };
+
+    match *value {
+        FieldValue::Number(number) => field
+            .try_downcast_mut::<f32>()
+            .is_some_and(|field| replace(field, number)),
+        FieldValue::Toggle(toggle) => field
+            .try_downcast_mut::<bool>()
+            .is_some_and(|field| replace(field, toggle)),
+        FieldValue::Rotation(degrees) => {
+            let [x, y, z] = degrees.map(f32::to_radians);
+            field
+                .try_downcast_mut::<Quat>()
+                .is_some_and(|field| replace(field, Quat::from_euler(EulerRot::XYZ, x, y, z)))
+        }
+        FieldValue::Color([red, green, blue, alpha]) => {
+            let color = Color::srgba(red, green, blue, alpha);
+            if let Some(field) = field.try_downcast_mut::<Color>() {
+                replace(field, color)
+            } else if let Some(field) = field.try_downcast_mut::<LinearRgba>() {
+                replace(field, color.into())
+            } else {
+                false
+            }
+        }
+        FieldValue::Text(_) => false,
+    }
+}
+
+/// Sets `field` to `value` and returns whether they differed.
+fn replace<T: PartialEq>(field: &mut T, value: T) -> bool {
+    let changed = *field != value;
+    *field = value;
+    changed
+}
+
+type InspectedParts = (
+    Entity,
+    Option<&'static Name>,
+    Option<&'static ChildOf>,
+    Option<&'static Transform>,
+    Option<&'static Mesh3d>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Option<&'static PointLight>,
+    (Has<Camera>, Has<DirectionalLight>, Has<SpotLight>),
+);
+
+fn send_inspector_snapshot(
+    entities: Query<InspectedParts, With<Transform>>,
+    children: Query<&Children>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut last: Local<InspectorSnapshot>,
+    mut snapshots: EventWriter<InspectorSnapshot>,
+) {
+    let inspect = |entity: Entity, depth: usize| -> Option<InspectorEntity> {
+        let (entity, name, _, transform, mesh, material, point_light, kind) =
+            entities.get(entity).ok()?;
+        let (camera, directional_light, spot_light) = kind;
+
+        let label = match name {
+            Some(name) => name.to_string(),
+            None if camera => format!("Camera {entity}"),
+            None if point_light.is_some() => format!("Point light {entity}"),
+            None if directional_light => format!("Directional light {entity}"),
+            None if spot_light => format!("Spot light {entity}"),
+            None if mesh.is_some() => format!("Mesh {entity}"),
+            None => format!("Entity {entity}"),
+        };
+
+        let mut components = Vec::new();
+        if let Some(transform) = transform {
+            components.push(InspectorComponent {
+                kind: ComponentKind::Transform,
+                fields: component_fields(transform),
+            });
+        }
+        if let Some(mesh) = mesh.and_then(|mesh| meshes.get(mesh)) {
+            let count = |path: &str, count: usize| InspectorField {
+                path: path.to_string(),
+                value: FieldValue::Text(count.to_string()),
+            };
+            components.push(InspectorComponent {
+                kind: ComponentKind::Mesh,
+                fields: vec![
+                    count("vertices", mesh.count_vertices()),
+                    count("indices", mesh.indices().map_or(0, |indices| indices.len())),
+                ],
+            });
+        }
+        if let Some(material) = material.and_then(|material| materials.get(material)) {
+            let ReflectRef::Struct(material) = material.reflect_ref() else {
+                unreachable!("StandardMaterial is a struct");
+            };
+            let mut fields = Vec::new();
+            for name in MATERIAL_FIELDS {
+                if let Some(field) = material.field(name) {
+                    collect_fields(field, format!(".{name}"), &mut fields);
+                }
+            }
+            components.push(InspectorComponent {
+                kind: ComponentKind::Material,
+                fields,
+            });
+        }
+        if let Some(point_light) = point_light {
+            components.push(InspectorComponent {
+                kind: ComponentKind::PointLight,
+                fields: component_fields(point_light),
+            });
+        }
+
+        Some(InspectorEntity {
+            entity,
+            label,
+            depth,
+            components,
+        })
+    };
+
+    let mut roots: Vec<Entity> = entities
+        .iter()
+        .filter(|(_, _, child_of, ..)| {
+            child_of.is_none_or(|child_of| !entities.contains(child_of.parent()))
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+    roots.sort();
+
+    let mut snapshot = InspectorSnapshot::default();
+    let mut stack: Vec<(Entity, usize)> = roots.into_iter().rev().map(|root| (root, 0)).collect();
+    while let Some((entity, depth)) = stack.pop() {
+        let Some(inspected) = inspect(entity, depth) else {
+            continue;
+        };
+        snapshot.0.push(inspected);
+
+        if let Ok(children) = children.get(entity) {
+            stack.extend(children.iter().rev().map(|child| (child, depth + 1)));
+        }
+    }
+
+    if *last != snapshot {
+        *last = snapshot.clone();
+        snapshots.write(snapshot);
+    }
+}
+
+type EditedParts = (
+    Option<&'static Transform>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Option<&'static PointLight>,
+);
+
+/// Turns `InspectorEdit`s that change something into `EditCommand`s, so they can be undone.
+fn apply_inspector_edits(
+    mut edits: EventReader<InspectorEdit>,
+    components: Query<EditedParts>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut commands: EventWriter<EditCommand>,
+) {
+    for edit in edits.read() {
+        let Ok((transform, material, point_light)) = components.get(edit.entity) else {
+            continue;
+        };
+
+        let current = match edit.component {
+            ComponentKind::Transform => transform.map(|transform| SceneEdit::Transform(*transform)),
+            ComponentKind::Material => material
+                .and_then(|material| materials.get(material))
+                .map(|material| SceneEdit::Material(Box::new(material.clone()))),
+            ComponentKind::PointLight => {
+                point_light.map(|point_light| SceneEdit::PointLight(*point_light))
+            }
+            ComponentKind::Mesh => None,
+        };
+
+        let Some(mut edited) = current else {
+            continue;
+        };
+        // Paths and values that don't fit the component would only leave empty steps to undo.
+        if edited
+            .value_mut()
+            .is_some_and(|value| set_field(value, &edit.path, &edit.value))
+        {
+            commands.write(EditCommand {
+                entity: edit.entity,
+                edit: edited,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{
+        ComponentKind, FieldValue, InspectorEdit, InspectorPlugin, InspectorSnapshot,
+        SNAPSHOT_FRAMES,
+    };
+    use crate::cube_color::Cube;
+    use crate::history::{History, HistoryCommand, HistoryPlugin};
+    use crate::scene_description::SceneDescription;
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    #[test]
+    fn edits_of_snapshotted_fields_can_be_undone() {
+        let scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        let mut app = headless_app(scene);
+        app.add_plugins((HistoryPlugin, InspectorPlugin));
+        app.finish();
+        app.cleanup();
+        // The first snapshot is taken before the scene is spawned.
+        for _ in 0..=SNAPSHOT_FRAMES {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let cube = world
+            .query_filtered::<Entity, With<Cube>>()
+            .single(world)
+            .unwrap();
+        let light = world
+            .query_filtered::<Entity, With<PointLight>>()
+            .single(world)
+            .unwrap();
+        let snapshot = world
+            .resource_mut::<Events<InspectorSnapshot>>()
+            .drain()
+            .last()
+            .unwrap();
+        assert_eq!(
+            snapshot.field(cube, ComponentKind::Transform, ".translation.y"),
+            Some(&FieldValue::Number(0.5))
+        );
+        assert_eq!(
+            snapshot.field(light, ComponentKind::PointLight, ".intensity"),
+            Some(&FieldValue::Number(1500.0))
+        );
+
+        let edit = |entity, component, path: &str, value| InspectorEdit {
+            entity,
+            component,
+            path: path.to_string(),
+            value,
+        };
+        app.world_mut().send_event_batch([
+            edit(
+                cube,
+                ComponentKind::Transform,
+                ".translation.x",
+                FieldValue::Number(2.0),
+            ),
+            edit(
+                light,
+                ComponentKind::PointLight,
+                ".intensity",
+                FieldValue::Number(3000.0),
+            ),
+            // Neither of these changes anything.
+            edit(
+                cube,
+                ComponentKind::Transform,
+                ".translation.w",
+                FieldValue::Number(1.0),
+            ),
+            edit(
+                light,
+                ComponentKind::PointLight,
+                ".intensity",
+                FieldValue::Toggle(true),
+            ),
+            edit(
+                cube,
+                ComponentKind::Transform,
+                ".scale.y",
+                FieldValue::Number(1.0),
+            ),
+        ]);
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<Transform>(cube).unwrap().translation.x, 2.0);
+        assert_eq!(world.get::<PointLight>(light).unwrap().intensity, 3000.0);
+
+        for _ in 0..2 {
+            app.world_mut().send_event(HistoryCommand::Undo);
+            app.update();
+        }
+        let world = app.world();
+        assert_eq!(world.get::<Transform>(cube).unwrap().translation.x, 0.0);
+        assert_eq!(world.get::<PointLight>(light).unwrap().intensity, 1500.0);
+        assert!(!world.resource::<History>().state().can_undo);
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::cube_color::Cube;
+use crate::history::{EditCommand, SceneEdit};
+
+/// Which part of the page has keyboard focus, kept in sync with the page by `SceneView`.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyboardFocus {
+    /// Nothing in particular, like after clicking the page background.
+    #[default]
+    Page,
+    /// The canvas of the scene.
+    Canvas,
+    /// An input, textarea, select or editable element of the page.
+    FormElement,
+}
+
+/// Keeps keyboard input away from the app unless its canvas has focus.
+///
+/// Keys held while focus moves to a form element would otherwise stay pressed, and anything typed
+/// there could move the scene. Systems that only make sense with the canvas focused can use the
+/// `canvas_focused` run condition on top.
+pub struct KeyboardFocusPlugin;
+
+impl Plugin for KeyboardFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyboardFocus>()
+            .add_systems(PreUpdate, route_keyboard_input.after(InputSystem));
+    }
+}
+
+/// Run condition for systems that react to the keyboard.
+pub fn canvas_focused(focus: Option<Res<KeyboardFocus>>) -> bool {
+    focus.is_some_and(|focus| *focus == KeyboardFocus::Canvas)
+}
+
+fn route_keyboard_input(
+    focus: Res<KeyboardFocus>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut events: ResMut<Events<KeyboardInput>>,
+) {
+    if *focus != KeyboardFocus::Canvas {
+        keys.reset_all();
+        events.clear();
+    }
+}
+
+/// How fast `move_cube_with_keys` moves cubes, in units per second.
+const CUBE_SPEED: f32 = 2.0;
+
+/// Moves cubes over the ground with WASD.
+pub fn move_cube_with_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    cubes: Query<(Entity, &Transform), With<Cube>>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    let direction = [
+        (KeyCode::KeyW, Vec3::NEG_Z),
+        (KeyCode::KeyA, Vec3::NEG_X),
+        (KeyCode::KeyS, Vec3::Z),
+        (KeyCode::KeyD, Vec3::X),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.pressed(*key))
+    .map(|(_, direction)| direction)
+    .sum::<Vec3>()
+    .normalize_or_zero();
+
+    if direction == Vec3::ZERO {
+        return;
+    }
+
+    for (entity, transform) in &cubes {
+        let mut transform = *transform;
+        transform.translation += direction * CUBE_SPEED * time.delta_secs();
+        edits.write(EditCommand {
+            entity,
+            edit: SceneEdit::Transform(transform),
+        });
+    }
+}
@@ -1,4 +1,27 @@
 pub mod app;
+pub mod collaboration;
+pub mod cube_color;
+pub mod history;
+pub mod inspector;
+pub mod keyboard_focus;
+pub mod lighting;
+pub mod material_editor;
+pub mod model_import;
+pub mod model_storage;
+pub mod orbit_camera;
+pub mod physics;
+pub mod primitives;
+pub mod scene_description;
+pub mod scene_events;
+pub mod scene_export;
+pub mod scene_storage;
+pub mod scene_text;
+pub mod scene_view;
+pub mod selection;
+pub mod simulation;
+pub mod thumbnail;
+pub mod transform_gizmo;
+pub mod viewport;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
@@ -0,0 +1,432 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::pbr::{
+    CascadeShadowConfig, CascadeShadowConfigBuilder, DirectionalLightShadowMap,
+    PointLightShadowMap, ShadowFilteringMethod,
+};
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+};
+
+use crate::history::{EditCommand, SceneChild, SceneEdit};
+use crate::orbit_camera::OrbitCamera;
+use crate::scene_description::{
+    DescribedScene, EnvironmentDescription, EnvironmentMapDescription, LightDescription,
+    SceneEnvironment, SceneReader, ShadowQuality, TransformDescription,
+};
+
+/// Width and height of each face of the generated environment maps.
+const SKY_SIZE: u32 = 32;
+
+/// The kinds of lights that can be added to the scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightKind {
+    Point,
+    Directional,
+    Spot,
+}
+
+impl LightKind {
+    pub const ALL: [LightKind; 3] = [LightKind::Point, LightKind::Directional, LightKind::Spot];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LightKind::Point => "Point light",
+            LightKind::Directional => "Directional light",
+            LightKind::Spot => "Spot light",
+        }
+    }
+
+    pub fn of(light: &LightDescription) -> Self {
+        match light {
+            LightDescription::Point { .. } => LightKind::Point,
+            LightDescription::Directional { .. } => LightKind::Directional,
+            LightDescription::Spot { .. } => LightKind::Spot,
+        }
+    }
+
+    /// A new light above and to the side of `focus`, shining at it.
+    fn light(self, focus: Vec3) -> LightDescription {
+        let transform = Transform::from_translation(focus + Vec3::new(3.0, 6.0, 3.0))
+            .looking_at(focus, Vec3::Y);
+        let transform = TransformDescription::from(&transform);
+
+        match self {
+            LightKind::Point => LightDescription::Point {
+                color: [1.0, 1.0, 1.0],
+                intensity: 1500.0,
+                range: 20.0,
+                shadows: false,
+                transform,
+            },
+            LightKind::Directional => LightDescription::Directional {
+                color: [1.0, 1.0, 1.0],
+                illuminance: light_consts::lux::OVERCAST_DAY,
+                shadows: false,
+                transform,
+            },
+            LightKind::Spot => LightDescription::Spot {
+                color: [1.0, 1.0, 1.0],
+                intensity: 3000.0,
+                range: 20.0,
+                inner_angle: 20.0,
+                outer_angle: 30.0,
+                shadows: false,
+                transform,
+            },
+        }
+    }
+}
+
+/// Changes to the lighting of the scene, sent from Leptos.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub enum LightingCommand {
+    /// Adds a light in front of the camera.
+    Add(LightKind),
+    Remove(Entity),
+    /// Gives a light of the same kind these properties, as an `EditCommand`. It stays where it is.
+    Edit {
+        entity: Entity,
+        light: LightDescription,
+    },
+    /// Changes the `SceneEnvironment`, as an `EditCommand`.
+    Environment(EnvironmentDescription),
+}
+
+/// The lights of the scene in the order of its children, and its environment.
+///
+/// Written whenever any of them changes.
+#[derive(Event, Clone, Debug, Default, PartialEq)]
+pub struct LightingState {
+    pub lights: Vec<(Entity, LightDescription)>,
+    pub environment: EnvironmentDescription,
+}
+
+/// Adds, removes and edits lights through `LightingCommand`s, and applies the `SceneEnvironment`
+/// to every 3D camera.
+///
+/// Shadow map sizes are resources, so with several scenes in one app the first one decides them.
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LightingCommand>()
+            .add_event::<LightingState>()
+            .add_systems(
+                Update,
+                (
+                    (apply_lighting_commands, report_lighting).chain(),
+                    apply_environment,
+                ),
+            );
+    }
+}
+
+type AnyLight = AnyOf<(
+    &'static PointLight,
+    &'static DirectionalLight,
+    &'static SpotLight,
+)>;
+
+fn apply_lighting_commands(
+    mut commands: Commands,
+    mut events: EventReader<LightingCommand>,
+    camera: Option<Single<(&OrbitCamera, &ChildOf)>>,
+    scenes: Query<Entity, With<SceneEnvironment>>,
+    lights: Query<AnyLight>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    for event in events.read() {
+        match event {
+            LightingCommand::Add(kind) => {
+                // The camera of a described scene is one of its children.
+                let Some((camera, ChildOf(scene))) = camera.as_deref().copied() else {
+                    continue;
+                };
+                edits.write(EditCommand {
+                    entity: commands.spawn_empty().id(),
+                    edit: SceneEdit::Child(Some((
+                        *scene,
+                        SceneChild::Light(kind.light(camera.focus())),
+                    ))),
+                });
+            }
+            LightingCommand::Remove(entity) => {
+                if lights.contains(*entity) {
+                    edits.write(EditCommand {
+                        entity: *entity,
+                        edit: SceneEdit::Child(None),
+                    });
+                }
+            }
+            LightingCommand::Edit { entity, light } => {
+                let Ok(current) = lights.get(*entity) else {
+                    continue;
+                };
+                if let Some(edit) = edited_light(current, light) {
+                    edits.write(EditCommand {
+                        entity: *entity,
+                        edit,
+                    });
+                }
+            }
+            LightingCommand::Environment(environment) => {
+                for scene in &scenes {
+                    edits.write(EditCommand {
+                        entity: scene,
+                        edit: SceneEdit::Environment(Box::new(environment.clone())),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// The light with the described properties, or `None` if it is of another kind.
+fn edited_light(
+    current: (
+        Option<&PointLight>,
+        Option<&DirectionalLight>,
+        Option<&SpotLight>,
+    ),
+    light: &LightDescription,
+) -> Option<SceneEdit> {
+    let srgb = |[red, green, blue]: [f32; 3]| Color::srgb(red, green, blue);
+
+    match (current, light) {
+        (
+            (Some(point), _, _),
+            &LightDescription::Point {
+                color,
+                intensity,
+                range,
+                shadows,
+                ..
+            },
+        ) => Some(SceneEdit::PointLight(PointLight {
+            color: srgb(color),
+            intensity,
+            range,
+            shadows_enabled: shadows,
+            ..*point
+        })),
+        (
+            (_, Some(directional), _),
+            &LightDescription::Directional {
+                color,
+                illuminance,
+                shadows,
+                ..
+            },
+        ) => Some(SceneEdit::DirectionalLight(DirectionalLight {
+            color: srgb(color),
+            illuminance,
+            shadows_enabled: shadows,
+            ..*directional
+        })),
+        (
+            (_, _, Some(spot)),
+            &LightDescription::Spot {
+                color,
+                intensity,
+                range,
+                inner_angle,
+                outer_angle,
+                shadows,
+                ..
+            },
+        ) => Some(SceneEdit::SpotLight(SpotLight {
+            color: srgb(color),
+            intensity,
+            range,
+            // Spot lights shine at most sideways, and never wider inside than outside.
+            inner_angle: inner_angle.min(outer_angle).min(90.0).to_radians(),
+            outer_angle: outer_angle.min(90.0).to_radians(),
+            shadows_enabled: shadows,
+            ..*spot
+        })),
+        _ => None,
+    }
+}
+
+fn report_lighting(
+    scenes: Query<(&SceneEnvironment, &Children), With<DescribedScene>>,
+    scene: SceneReader,
+    mut last: Local<Option<LightingState>>,
+    mut states: EventWriter<LightingState>,
+) {
+    let Some((SceneEnvironment(environment), children)) = scenes.iter().next() else {
+        return;
+    };
+    let state = LightingState {
+        lights: children
+            .iter()
+            .filter_map(|child| Some((child, scene.light(child)?)))
+            .collect(),
+        environment: environment.clone(),
+    };
+
+    if last.as_ref() != Some(&state) {
+        states.write(state.clone());
+        *last = Some(state);
+    }
+}
+
+/// Applies the environment when it changes, and to cameras and lights added since.
+fn apply_environment(
+    mut commands: Commands,
+    scenes: Query<Ref<SceneEnvironment>>,
+    cameras: Query<(Entity, Ref<Camera3d>)>,
+    directional_lights: Query<(Entity, Ref<DirectionalLight>)>,
+    mut images: ResMut<Assets<Image>>,
+    mut sky: Local<Option<(EnvironmentMapDescription, Handle<Image>)>>,
+) {
+    let Some(environment) = scenes.iter().next() else {
+        return;
+    };
+    let changed = environment.is_changed();
+    let SceneEnvironment(environment) = &*environment;
+    let quality = environment.shadow_quality;
+
+    if changed {
+        commands.insert_resource(DirectionalLightShadowMap {
+            size: match quality {
+                ShadowQuality::Low => 1024,
+                ShadowQuality::Medium => 2048,
+                ShadowQuality::High => 4096,
+            },
+        });
+        commands.insert_resource(PointLightShadowMap {
+            size: match quality {
+                ShadowQuality::Low => 512,
+                ShadowQuality::Medium => 1024,
+                ShadowQuality::High => 2048,
+            },
+        });
+    }
+
+    let environment_map = environment.environment_map.map(|description| {
+        // The sky only has to be drawn again when its colors change.
+        let colors = EnvironmentMapDescription {
+            intensity: 0.0,
+            ..description
+        };
+        let image = match &*sky {
+            Some((drawn, image)) if *drawn == colors => image.clone(),
+            _ => {
+                let image = images.add(sky_cubemap(&description));
+                *sky = Some((colors, image.clone()));
+                image
+            }
+        };
+
+        EnvironmentMapLight {
+            diffuse_map: image.clone(),
+            specular_map: image,
+            intensity: description.intensity,
+            ..default()
+        }
+    });
+
+    for (camera, added) in &cameras {
+        if !changed && !added.is_added() {
+            continue;
+        }
+
+        let ambient = environment.ambient.unwrap_or_default();
+        let [red, green, blue] = ambient.color;
+        let mut camera = commands.entity(camera);
+        camera.insert((
+            AmbientLight {
+                color: Color::srgb(red, green, blue),
+                brightness: match environment.ambient {
+                    Some(ambient) => ambient.brightness,
+                    None => 0.0,
+                },
+                ..default()
+            },
+            match quality {
+                ShadowQuality::Low => ShadowFilteringMethod::Hardware2x2,
+                ShadowQuality::Medium | ShadowQuality::High => ShadowFilteringMethod::Gaussian,
+            },
+        ));
+        match &environment_map {
+            Some(environment_map) => camera.insert(environment_map.clone()),
+            None => camera.remove::<EnvironmentMapLight>(),
+        };
+    }
+
+    for (light, added) in &directional_lights {
+        if changed || added.is_added() {
+            commands.entity(light).insert(cascades(quality));
+        }
+    }
+}
+
+/// How far directional lights cast shadows, and in how many steps of detail.
+fn cascades(quality: ShadowQuality) -> CascadeShadowConfig {
+    let (num_cascades, maximum_distance) = match quality {
+        ShadowQuality::Low => (1, 20.0),
+        ShadowQuality::Medium => (2, 40.0),
+        ShadowQuality::High => (4, 100.0),
+    };
+
+    CascadeShadowConfigBuilder {
+        num_cascades,
+        maximum_distance,
+        ..default()
+    }
+    .build()
+}
+
+/// A cubemap of the sky, fading from the ground color below the horizon to the sky color above.
+fn sky_cubemap(sky: &EnvironmentMapDescription) -> Image {
+    let srgb = |[red, green, blue]: [f32; 3]| Vec3::new(red, green, blue);
+    let (ground, horizon, zenith) = (srgb(sky.ground), srgb(sky.horizon), srgb(sky.sky));
+
+    let mut data = Vec::with_capacity((SKY_SIZE * SKY_SIZE * 6 * 4) as usize);
+    // Faces in the order +X, -X, +Y, -Y, +Z, -Z. Only the height of a direction matters, which
+    // is the same for all faces around the horizon.
+    for face in 0..6 {
+        for row in 0..SKY_SIZE {
+            let v = (row as f32 + 0.5) / SKY_SIZE as f32 * 2.0 - 1.0;
+
+            for column in 0..SKY_SIZE {
+                let u = (column as f32 + 0.5) / SKY_SIZE as f32 * 2.0 - 1.0;
+                let direction = match face {
+                    2 => Vec3::new(u, 1.0, v),
+                    3 => Vec3::new(u, -1.0, -v),
+                    _ => Vec3::new(1.0, -v, u),
+                };
+                let y = direction.normalize().y;
+                let color = if y >= 0.0 {
+                    horizon.lerp(zenith, y.sqrt())
+                } else {
+                    horizon.lerp(ground, (-y).sqrt())
+                };
+
+                let [red, green, blue] = color
+                    .to_array()
+                    .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+                data.extend([red, green, blue, 255]);
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: SKY_SIZE,
+            height: SKY_SIZE,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    image
+}
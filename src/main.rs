@@ -2,23 +2,56 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
+    use axum::routing::get;
     use axum::Router;
     use leptos::logging::log;
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use bevytos::app::*;
+    use bevytos::collaboration::relay::{self, Rooms};
+    use bevytos::model_storage::store::{self as model_store, ModelStore};
+    use bevytos::scene_description::SceneDescription;
+    use bevytos::scene_storage::store::SceneStore;
+    use bevytos::thumbnail::cache::{self, ThumbnailCache};
 
     let conf = get_configuration(None).unwrap();
     let addr = conf.leptos_options.site_addr;
     let leptos_options = conf.leptos_options;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
+    let scene_store = SceneStore::from_env();
+    let model_store = ModelStore::from_env();
+    // With `SIMULATE_ROOMS` set, the server simulates the scene of each room
+    let rooms = if std::env::var_os("SIMULATE_ROOMS").is_some() {
+        let path = std::path::Path::new(&*leptos_options.site_root).join(DEFAULT_SCENE);
+        let text = std::fs::read_to_string(path).expect("the default scene can be read");
+        Rooms::simulated(SceneDescription::from_ron(&text).expect("the default scene is valid"))
+    } else {
+        Rooms::default()
+    };
 
     let app = Router::new()
-        .leptos_routes(&leptos_options, routes, {
-            let leptos_options = leptos_options.clone();
-            move || shell(leptos_options.clone())
-        })
+        .route("/ws/{room}", get(relay::connect).with_state(rooms))
+        .route(
+            "/thumb/{file}",
+            get(cache::serve).with_state(ThumbnailCache::from_env(scene_store.clone())),
+        )
+        .route(
+            "/models/{file}",
+            get(model_store::serve).with_state(model_store.clone()),
+        )
+        .leptos_routes_with_context(
+            &leptos_options,
+            routes,
+            move || {
+                provide_context(scene_store.clone());
+                provide_context(model_store.clone());
+            },
+            {
+                let leptos_options = leptos_options.clone();
+                move || shell(leptos_options.clone())
+            },
+        )
         .fallback(leptos_axum::file_and_error_handler(shell))
         .with_state(leptos_options);
 
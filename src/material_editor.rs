@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+
+use crate::history::{EditCommand, SceneEdit};
+use crate::scene_description::{MaterialDescription, MaterialLibrary, ObjectAssets, SceneReader};
+use crate::selection::Selection;
+
+/// Images of the site that materials can be textured with, as asset paths.
+pub const BUNDLED_TEXTURES: [&str; 2] = ["textures/checker.png", "textures/tiles_normal.png"];
+
+/// Gives the material of `entity` these properties.
+///
+/// The name decides which material that is: a name from the `MaterialLibrary` switches to that
+/// material, a new one names the current material, and no name gives the entity a material of
+/// its own. Names themselves are not undone, the properties and the switching are.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MaterialEdit {
+    pub entity: Entity,
+    pub material: MaterialDescription,
+}
+
+/// The material of the selected entity, written whenever it or the selection changes.
+#[derive(Event, Clone, Debug, Default, PartialEq)]
+pub struct SelectedMaterial {
+    pub entity: Option<Entity>,
+    pub material: Option<MaterialDescription>,
+    /// How many entities share the material, including the selected one.
+    pub users: usize,
+    /// The names in the `MaterialLibrary`, in alphabetical order.
+    pub library: Vec<String>,
+}
+
+/// Edits the `StandardMaterial` of the selected entity through `MaterialEdit`s, shown as
+/// `SelectedMaterial`.
+pub struct MaterialEditorPlugin;
+
+impl Plugin for MaterialEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MaterialEdit>()
+            .add_event::<SelectedMaterial>()
+            .add_systems(
+                Update,
+                (apply_material_edits, report_selected_material).chain(),
+            );
+    }
+}
+
+fn apply_material_edits(
+    mut edits: EventReader<MaterialEdit>,
+    handles: Query<&MeshMaterial3d<StandardMaterial>>,
+    mut assets: ObjectAssets,
+    mut commands: EventWriter<EditCommand>,
+) {
+    for MaterialEdit { entity, material } in edits.read() {
+        let Ok(handle) = handles.get(*entity) else {
+            continue;
+        };
+        let Some(current) = assets.materials.get(&handle.0).cloned() else {
+            continue;
+        };
+
+        let name = material
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty());
+        let current_name = assets.library.name_of(&handle.0).map(str::to_string);
+
+        let mut edited = current.clone();
+        material.apply(&mut edited, &assets.asset_server);
+
+        if let Some(shared) = name
+            .filter(|name| Some(*name) != current_name.as_deref())
+            .and_then(|name| assets.library.get(name))
+        {
+            commands.write(EditCommand {
+                entity: *entity,
+                edit: SceneEdit::MaterialHandle(shared.clone()),
+            });
+            continue;
+        }
+
+        match (name, current_name.as_deref()) {
+            (None, Some(_)) => {
+                // Leaves the named material to the other entities that use it.
+                let own = assets.materials.add(edited);
+                commands.write(EditCommand {
+                    entity: *entity,
+                    edit: SceneEdit::MaterialHandle(own),
+                });
+                continue;
+            }
+            (Some(name), Some(current_name)) if name != current_name => {
+                assets.library.rename(current_name, name.to_string());
+            }
+            (Some(name), None) => assets.library.insert(name.to_string(), handle.0.clone()),
+            _ => {}
+        }
+
+        if MaterialDescription::from(&current) != MaterialDescription::from(&edited) {
+            commands.write(EditCommand {
+                entity: *entity,
+                edit: SceneEdit::Material(Box::new(edited)),
+            });
+        }
+    }
+}
+
+fn report_selected_material(
+    selection: Res<Selection>,
+    handles: Query<&MeshMaterial3d<StandardMaterial>>,
+    scene: SceneReader,
+    library: Res<MaterialLibrary>,
+    mut last: Local<Option<SelectedMaterial>>,
+    mut events: EventWriter<SelectedMaterial>,
+) {
+    let handle = selection.0.and_then(|entity| handles.get(entity).ok());
+    let selected = SelectedMaterial {
+        entity: selection.0,
+        material: handle.and_then(|handle| scene.material(&handle.0)),
+        users: handle.map_or(0, |handle| {
+            let id = handle.0.id();
+            handles.iter().filter(|other| other.0.id() == id).count()
+        }),
+        library: library.names().map(str::to_string).collect(),
+    };
+
+    if last.as_ref() != Some(&selected) {
+        events.write(selected.clone());
+        *last = Some(selected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{MaterialEdit, MaterialEditorPlugin};
+    use crate::history::HistoryPlugin;
+    use crate::scene_description::{
+        MaterialDescription, MaterialLibrary, MeshDescription, ObjectDescription, SceneDescription,
+        TransformDescription,
+    };
+    use crate::selection::Selection;
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    /// The default scene with two balls that share the material "Red".
+    fn app() -> App {
+        let mut scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        for (name, x) in [("Left", -2.0), ("Right", 2.0)] {
+            scene.objects.push(ObjectDescription {
+                name: Some(name.to_string()),
+                mesh: MeshDescription::Sphere { radius: 0.5 },
+                material: MaterialDescription {
+                    name: Some("Red".to_string()),
+                    base_color: [1.0, 0.0, 0.0, 1.0],
+                    ..default()
+                },
+                transform: TransformDescription {
+                    translation: [x, 0.5, 0.0],
+                    ..default()
+                },
+                roles: Vec::new(),
+                body: None,
+            });
+        }
+
+        let mut app = headless_app(scene);
+        app.init_resource::<Selection>()
+            .add_plugins((HistoryPlugin, MaterialEditorPlugin));
+        app.finish();
+        app.cleanup();
+        app.update();
+        app
+    }
+
+    fn entity(app: &mut App, name: &str) -> Entity {
+        let world = app.world_mut();
+        world
+            .query::<(Entity, &Name)>()
+            .iter(world)
+            .find(|(_, other)| other.as_str() == name)
+            .map(|(entity, _)| entity)
+            .unwrap()
+    }
+
+    fn material(app: &App, entity: Entity) -> Handle<StandardMaterial> {
+        app.world()
+            .get::<MeshMaterial3d<StandardMaterial>>(entity)
+            .unwrap()
+            .0
+            .clone()
+    }
+
+    fn base_color(app: &App, entity: Entity) -> Color {
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        materials.get(&material(app, entity)).unwrap().base_color
+    }
+
+    fn edit(app: &mut App, entity: Entity, name: Option<&str>, base_color: [f32; 4]) {
+        app.world_mut().send_event(MaterialEdit {
+            entity,
+            material: MaterialDescription {
+                name: name.map(str::to_string),
+                base_color,
+                ..default()
+            },
+        });
+        app.update();
+    }
+
+    #[test]
+    fn naming_a_library_material_switches_to_it() {
+        let mut app = app();
+        let (cube, left) = (entity(&mut app, "Cube"), entity(&mut app, "Left"));
+
+        edit(&mut app, cube, Some("Red"), [0.0, 0.0, 1.0, 1.0]);
+
+        assert_eq!(material(&app, cube), material(&app, left));
+        assert_eq!(base_color(&app, cube), Color::srgb(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn renaming_a_shared_material_keeps_it_shared() {
+        let mut app = app();
+        let (left, right) = (entity(&mut app, "Left"), entity(&mut app, "Right"));
+        let shared = material(&app, left);
+
+        edit(&mut app, left, Some("Crimson"), [0.6, 0.0, 0.1, 1.0]);
+
+        let library = app.world().resource::<MaterialLibrary>();
+        assert_eq!(library.get("Red"), None);
+        assert_eq!(library.get("Crimson"), Some(&shared));
+        assert_eq!(material(&app, right), shared);
+        assert_eq!(base_color(&app, right), Color::srgb(0.6, 0.0, 0.1));
+    }
+
+    #[test]
+    fn clearing_the_name_detaches_a_material_of_its_own() {
+        let mut app = app();
+        let (left, right) = (entity(&mut app, "Left"), entity(&mut app, "Right"));
+        let shared = material(&app, right);
+
+        edit(&mut app, left, None, [0.0, 1.0, 0.0, 1.0]);
+
+        assert_ne!(material(&app, left), shared);
+        assert_eq!(material(&app, right), shared);
+        assert_eq!(base_color(&app, left), Color::srgb(0.0, 1.0, 0.0));
+        assert_eq!(base_color(&app, right), Color::srgb(1.0, 0.0, 0.0));
+        let library = app.world().resource::<MaterialLibrary>();
+        assert_eq!(library.name_of(&material(&app, left)), None);
+        assert_eq!(library.get("Red"), Some(&shared));
+    }
+}
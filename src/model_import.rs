@@ -0,0 +1,112 @@
+use bevy::gltf::GltfLoaderSettings;
+use bevy::prelude::*;
+
+use crate::model_storage::ModelInfo;
+use crate::orbit_camera::OrbitCamera;
+
+/// Adds a stored model to the scene, sent from Leptos once it is uploaded.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct ImportModel(pub ModelInfo);
+
+/// Marks the root of an imported glTF scene.
+#[derive(Component, Clone, Debug)]
+pub struct ImportedModel(pub ModelInfo);
+
+/// Loads `ImportModel`s from the server and spawns their first glTF scene, without its cameras,
+/// where the camera looks. Models imported while no scene is spawned wait for the next one.
+///
+/// Like other objects added later, the models are children of the `DescribedScene` and go away
+/// when another scene is loaded.
+pub struct ModelImportPlugin;
+
+impl Plugin for ModelImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ImportModel>()
+            .add_systems(Update, import_models);
+    }
+}
+
+fn import_models(
+    mut commands: Commands,
+    mut events: EventReader<ImportModel>,
+    mut pending: Local<Vec<ModelInfo>>,
+    camera: Option<Single<(&OrbitCamera, &ChildOf)>>,
+    asset_server: Res<AssetServer>,
+) {
+    // Events only last two updates, so they are kept until there is a scene to add them to.
+    pending.extend(events.read().map(|ImportModel(model)| model.clone()));
+
+    // The camera of a described scene is one of its children.
+    let Some(camera) = camera else {
+        return;
+    };
+    let (camera, ChildOf(scene)) = *camera;
+
+    for model in pending.drain(..) {
+        let gltf_scene = GltfAssetLabel::Scene(0).from_asset(model.path.clone());
+        commands.spawn((
+            // Cameras in the model would render over the orbit camera, so they are left out.
+            SceneRoot(
+                asset_server.load_with_settings(gltf_scene, |settings: &mut GltfLoaderSettings| {
+                    settings.load_cameras = false
+                }),
+            ),
+            Transform::from_translation(camera.focus()),
+            Name::new(model.name.clone()),
+            ImportedModel(model),
+            ChildOf(*scene),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::{ImportModel, ImportedModel, ModelImportPlugin};
+    use crate::model_storage::ModelInfo;
+    use crate::orbit_camera::OrbitCamera;
+
+    #[test]
+    fn models_imported_without_a_scene_wait_for_one() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Scene>()
+            .add_plugins(ModelImportPlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                20,
+            )));
+        let model = ModelInfo {
+            name: "Duck.glb".to_string(),
+            path: "models/0192a3b4c5d6.glb".to_string(),
+        };
+        app.world_mut().send_event(ImportModel(model.clone()));
+        // Events are only dropped once fixed updates ran.
+        for _ in 0..3 {
+            app.update();
+        }
+        let mut models = app
+            .world_mut()
+            .query::<(&ImportedModel, &ChildOf, &Transform)>();
+        assert_eq!(models.iter(app.world()).count(), 0);
+
+        let scene = app.world_mut().spawn_empty().id();
+        let focus = Vec3::new(1.0, 0.0, 0.0);
+        app.world_mut().spawn((
+            OrbitCamera::new(Vec3::new(1.0, 2.0, 5.0), focus),
+            ChildOf(scene),
+        ));
+        app.update();
+
+        let imported: Vec<_> = models
+            .iter(app.world())
+            .map(|(ImportedModel(model), ChildOf(parent), transform)| {
+                (model.clone(), *parent, transform.translation)
+            })
+            .collect();
+        assert_eq!(imported, [(model, scene, focus)]);
+    }
+}
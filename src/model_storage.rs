@@ -0,0 +1,290 @@
+use leptos::prelude::*;
+use leptos::server_fn::codec::{MultipartData, MultipartFormData};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Models larger than this are rejected, before uploading them if possible.
+pub const MAX_MODEL_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Where stored models are served, as an asset path below the site root.
+pub const MODELS_PATH: &str = "models";
+
+/// A stored model.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// The name of the uploaded file.
+    pub name: String,
+    /// The asset path of the stored file, like `models/0192a3b4c5d6.glb`.
+    pub path: String,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    #[error("{0} is not a .glb or .gltf file")]
+    UnsupportedFile(String),
+    #[error("the model is larger than {} MB", MAX_MODEL_SIZE / 1024 / 1024)]
+    TooLarge,
+    #[error("no file was uploaded")]
+    Missing,
+    #[error("the model is not valid glTF: {0}")]
+    Invalid(String),
+    #[error("the model refers to {0}, which isn't part of the upload; try a .glb file instead")]
+    ExternalFile(String),
+}
+
+/// The two ways of storing a glTF model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFormat {
+    /// Binary glTF, with everything in one file.
+    Glb,
+    /// JSON glTF; buffers and images must be embedded as data URIs.
+    Gltf,
+}
+
+impl ModelFormat {
+    pub fn from_file_name(name: &str) -> Result<Self, ModelError> {
+        let name_lowercase = name.to_ascii_lowercase();
+        if name_lowercase.ends_with(".glb") {
+            Ok(ModelFormat::Glb)
+        } else if name_lowercase.ends_with(".gltf") {
+            Ok(ModelFormat::Gltf)
+        } else {
+            Err(ModelError::UnsupportedFile(name.to_string()))
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ModelFormat::Glb => "glb",
+            ModelFormat::Gltf => "gltf",
+        }
+    }
+}
+
+/// Stores the model in the first file of `data` and returns where it can be loaded from.
+#[server(input = MultipartFormData)]
+pub async fn upload_model(data: MultipartData) -> Result<ModelInfo, ServerFnError> {
+    let store = expect_context::<store::ModelStore>();
+    let mut data = data
+        .into_inner()
+        .expect("multipart data is read on the server");
+
+    let mut field = data.next_field().await?.ok_or(ModelError::Missing)?;
+    let name = field.file_name().unwrap_or_default().to_string();
+    let format = ModelFormat::from_file_name(&name)?;
+
+    // Read in chunks, so oversized uploads are cut off early.
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > MAX_MODEL_SIZE {
+            return Err(ModelError::TooLarge.into());
+        }
+    }
+
+    validate_model(format, &bytes)?;
+    Ok(store.save(&name, format, &bytes).await?)
+}
+
+/// Checks that `bytes` hold a glTF 2.0 model in `format` that doesn't need any other files.
+#[cfg(feature = "ssr")]
+pub fn validate_model(format: ModelFormat, bytes: &[u8]) -> Result<(), ModelError> {
+    let is_binary = bytes.starts_with(b"glTF");
+    if is_binary != (format == ModelFormat::Glb) {
+        return Err(ModelError::Invalid(format!(
+            "the contents don't match the .{} extension",
+            format.extension()
+        )));
+    }
+
+    let gltf = gltf::Gltf::from_slice(bytes).map_err(|err| ModelError::Invalid(err.to_string()))?;
+
+    let uris = gltf
+        .buffers()
+        .filter_map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Uri(uri) => Some(uri),
+            gltf::buffer::Source::Bin => None,
+        })
+        .chain(gltf.images().filter_map(|image| match image.source() {
+            gltf::image::Source::Uri { uri, .. } => Some(uri),
+            gltf::image::Source::View { .. } => None,
+        }));
+    for uri in uris {
+        if !uri.starts_with("data:") {
+            return Err(ModelError::ExternalFile(uri.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// -------- Storage --------
+#[cfg(feature = "ssr")]
+pub mod store {
+    use std::io::{Error, ErrorKind};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use axum::extract::{Path, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use tokio::fs;
+
+    use super::{ModelFormat, ModelInfo, MODELS_PATH};
+
+    /// Keeps uploaded models as `<id>.glb` or `<id>.gltf` files in a directory.
+    ///
+    /// Ids are hex timestamps like those of `SceneStore`; models are never changed once stored.
+    #[derive(Clone, Debug)]
+    pub struct ModelStore {
+        dir: PathBuf,
+    }
+
+    impl ModelStore {
+        pub fn new(dir: impl Into<PathBuf>) -> Self {
+            Self { dir: dir.into() }
+        }
+
+        /// Uses the directory in `MODEL_DIR`, or `data/models`.
+        pub fn from_env() -> Self {
+            Self::new(std::env::var("MODEL_DIR").unwrap_or_else(|_| "data/models".to_string()))
+        }
+
+        pub async fn save(
+            &self,
+            name: &str,
+            format: ModelFormat,
+            bytes: &[u8],
+        ) -> Result<ModelInfo, Error> {
+            fs::create_dir_all(&self.dir).await?;
+
+            let mut millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(Error::other)?
+                .as_millis();
+            // The model is written next to the others first and then linked into place, so a
+            // stored model is complete as soon as it can be served. Linking fails for ids that
+            // are taken, so models uploaded at the same time get different ones.
+            static PARTS: AtomicU64 = AtomicU64::new(0);
+            let part = self.dir.join(format!(
+                "{}-{}.part",
+                std::process::id(),
+                PARTS.fetch_add(1, Ordering::Relaxed)
+            ));
+            let claimed = match fs::write(&part, bytes).await {
+                Ok(()) => loop {
+                    let file = format!("{millis:012x}.{}", format.extension());
+                    match fs::hard_link(&part, self.dir.join(&file)).await {
+                        Ok(()) => break Ok(file),
+                        Err(err) if err.kind() == ErrorKind::AlreadyExists => millis += 1,
+                        Err(err) => break Err(err),
+                    }
+                },
+                Err(err) => Err(err),
+            };
+            fs::remove_file(&part).await.ok();
+            let file = claimed?;
+
+            Ok(ModelInfo {
+                name: name.to_string(),
+                path: format!("{MODELS_PATH}/{file}"),
+            })
+        }
+
+        /// The stored file with this name, or `None` if there is none.
+        pub async fn load(&self, file: &str) -> Result<Option<(ModelFormat, Vec<u8>)>, Error> {
+            // File names come from the client, so anything that could leave the directory is
+            // rejected.
+            let is_id = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit());
+            let format = match file.split_once('.') {
+                Some((id, "glb")) if is_id(id) => ModelFormat::Glb,
+                Some((id, "gltf")) if is_id(id) => ModelFormat::Gltf,
+                _ => return Ok(None),
+            };
+
+            match fs::read(self.dir.join(file)).await {
+                Ok(bytes) => Ok(Some((format, bytes))),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+    }
+
+    /// Serves `/models/{file}`.
+    pub async fn serve(Path(file): Path<String>, State(store): State<ModelStore>) -> Response {
+        match store.load(&file).await {
+            Ok(Some((format, bytes))) => {
+                let content_type = match format {
+                    ModelFormat::Glb => "model/gltf-binary",
+                    ModelFormat::Gltf => "model/gltf+json",
+                };
+                ([(header::CONTENT_TYPE, content_type)], bytes).into_response()
+            }
+            Ok(None) => StatusCode::NOT_FOUND.into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::BTreeSet;
+
+        use super::{ModelFormat, ModelStore, MODELS_PATH};
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn models_uploaded_at_once_get_their_own_files() {
+            let dir = std::env::temp_dir().join(format!("bevytos-models-{}", std::process::id()));
+            let store = ModelStore::new(&dir);
+
+            let saves: Vec<_> = (0..16u8)
+                .map(|index| {
+                    let store = store.clone();
+                    tokio::spawn(async move {
+                        let info = store.save("model.glb", ModelFormat::Glb, &[index]).await;
+                        (index, info.unwrap())
+                    })
+                })
+                .collect();
+            // Every model that can be served while the others are uploaded is already complete.
+            let mut unfinished = Vec::new();
+            while !saves.iter().all(|save| save.is_finished()) {
+                let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+                    continue;
+                };
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if let Ok(Some((_, bytes))) = store.load(&name).await {
+                        if bytes.is_empty() {
+                            unfinished.push(name);
+                        }
+                    }
+                }
+            }
+            let mut saved = Vec::new();
+            for save in saves {
+                saved.push(save.await.unwrap());
+            }
+
+            let mut loaded = Vec::new();
+            for (index, info) in &saved {
+                let file = info.path.strip_prefix(&format!("{MODELS_PATH}/")).unwrap();
+                loaded.push((*index, store.load(file).await.unwrap()));
+            }
+            let mut files = tokio::fs::read_dir(&dir).await.unwrap();
+            let mut file_count = 0;
+            while files.next_entry().await.unwrap().is_some() {
+                file_count += 1;
+            }
+            tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+            assert_eq!(unfinished, Vec::<String>::new());
+            assert_eq!(file_count, saved.len());
+            let paths: BTreeSet<_> = saved.iter().map(|(_, info)| info.path.clone()).collect();
+            assert_eq!(paths.len(), saved.len());
+            for (index, model) in loaded {
+                assert_eq!(model, Some((ModelFormat::Glb, vec![index])));
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+use crate::selection::{PointerCaptured, Selection};
+
+/// Camera actions that can be triggered from Leptos.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraCommand {
+    /// Move the camera back to where it was spawned.
+    Reset,
+    /// Orbit around the selected entity and zoom so it fills the view.
+    FrameSelected,
+}
+
+/// Where an orbit camera is looking from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OrbitState {
+    focus: Vec3,
+    radius: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl OrbitState {
+    fn looking_at(eye: Vec3, focus: Vec3) -> Self {
+        let offset = eye - focus;
+        let radius = offset.length();
+
+        Self {
+            focus,
+            radius,
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / radius).asin(),
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        let direction = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+
+        Transform::from_translation(self.focus + direction * self.radius)
+            .looking_at(self.focus, Vec3::Y)
+    }
+
+    fn lerp(&self, target: &Self, t: f32) -> Self {
+        Self {
+            focus: self.focus.lerp(target.focus, t),
+            radius: self.radius.lerp(target.radius, t),
+            yaw: self.yaw.lerp(target.yaw, t),
+            pitch: self.pitch.lerp(target.pitch, t),
+        }
+    }
+}
+
+/// Orbits, pans and zooms a camera around a focus point.
+///
+/// Drag with the left mouse button or one finger to orbit, with the right or middle mouse button
+/// or two fingers to pan, and use the wheel or a pinch to zoom. Input is only picked up while the
+/// pointer is over the canvas, so the page keeps scrolling normally everywhere else.
+#[derive(Component, Clone, Debug)]
+#[require(Camera3d)]
+pub struct OrbitCamera {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Pitch limit in radians, both above and below the horizon.
+    pub max_pitch: f32,
+    /// Radians per pixel dragged.
+    pub orbit_speed: f32,
+    /// Fraction of the radius per pixel dragged.
+    pub pan_speed: f32,
+    /// Fraction of the radius per wheel line.
+    pub zoom_speed: f32,
+    /// Share of the remaining movement that is left after 1/60 s. `0.0` disables smoothing.
+    pub damping: f32,
+    current: OrbitState,
+    target: OrbitState,
+    home: OrbitState,
+}
+
+impl OrbitCamera {
+    pub fn new(eye: Vec3, focus: Vec3) -> Self {
+        let state = OrbitState::looking_at(eye, focus);
+
+        Self {
+            min_radius: 1.0,
+            max_radius: 50.0,
+            max_pitch: 85_f32.to_radians(),
+            orbit_speed: 0.01,
+            pan_speed: 0.002,
+            zoom_speed: 0.1,
+            damping: 0.8,
+            current: state,
+            target: state,
+            home: state,
+        }
+    }
+
+    /// The camera transform for the current, smoothed state.
+    pub fn transform(&self) -> Transform {
+        self.current.transform()
+    }
+
+    /// Where the camera ends up once smoothing has caught up.
+    pub fn eye(&self) -> Vec3 {
+        self.target.transform().translation
+    }
+
+    /// The point the camera orbits around once smoothing has caught up.
+    pub fn focus(&self) -> Vec3 {
+        self.target.focus
+    }
+
+    fn orbit(&mut self, delta: Vec2) {
+        self.target.yaw -= delta.x * self.orbit_speed;
+        self.target.pitch =
+            (self.target.pitch + delta.y * self.orbit_speed).clamp(-self.max_pitch, self.max_pitch);
+    }
+
+    fn pan(&mut self, delta: Vec2) {
+        let transform = self.target.transform();
+        let scale = self.target.radius * self.pan_speed;
+
+        self.target.focus += (transform.up() * delta.y - transform.right() * delta.x) * scale;
+    }
+
+    fn zoom(&mut self, factor: f32) {
+        self.target.radius = (self.target.radius * factor).clamp(self.min_radius, self.max_radius);
+    }
+}
+
+/// Adds the `OrbitCamera` controller and handles `CameraCommand`s.
+pub struct OrbitCameraPlugin;
+
+/// The systems moving orbit cameras, for ordering input handling that comes first.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OrbitCameraSystems;
+
+impl Plugin for OrbitCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraCommand>().add_systems(
+            Update,
+            (
+                orbit_mouse_input,
+                orbit_touch_input,
+                handle_camera_commands,
+                apply_orbit,
+            )
+                .chain()
+                .in_set(OrbitCameraSystems),
+        );
+    }
+}
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+fn orbit_mouse_input(
+    window: Single<&Window, With<PrimaryWindow>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+    captured: Option<Res<PointerCaptured>>,
+    mut dragging: Local<bool>,
+    mut cameras: Query<&mut OrbitCamera>,
+) {
+    let over_canvas = window.cursor_position().is_some();
+
+    // A drag keeps going when it leaves the canvas, but it has to start on it.
+    if buttons.any_just_pressed(MOUSE_BUTTONS) {
+        *dragging = over_canvas && !captured.is_some_and(|captured| captured.0);
+    } else if !buttons.any_pressed(MOUSE_BUTTONS) {
+        *dragging = false;
+    }
+
+    let lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / 100.0,
+    };
+
+    for mut camera in &mut cameras {
+        if *dragging && buttons.pressed(MouseButton::Left) {
+            camera.orbit(motion.delta);
+        } else if *dragging {
+            camera.pan(motion.delta);
+        }
+
+        if over_canvas && lines != 0.0 {
+            let factor = (1.0 - camera.zoom_speed).powf(lines);
+            camera.zoom(factor);
+        }
+    }
+}
+
+fn orbit_touch_input(touches: Res<Touches>, mut cameras: Query<&mut OrbitCamera>) {
+    let active: Vec<_> = touches.iter().collect();
+
+    for mut camera in &mut cameras {
+        match active.as_slice() {
+            [touch] => camera.orbit(touch.delta()),
+            [first, second] => {
+                camera.pan((first.delta() + second.delta()) / 2.0);
+
+                let before = first
+                    .previous_position()
+                    .distance(second.previous_position());
+                let after = first.position().distance(second.position());
+                if before > 0.0 && after > 0.0 {
+                    camera.zoom(before / after);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_camera_commands(
+    mut commands: EventReader<CameraCommand>,
+    selection: Option<Res<Selection>>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+    mut cameras: Query<(&mut OrbitCamera, &Projection)>,
+) {
+    for command in commands.read() {
+        for (mut camera, projection) in &mut cameras {
+            match command {
+                CameraCommand::Reset => camera.target = camera.home,
+                CameraCommand::FrameSelected => {
+                    let selected = selection.as_ref().and_then(|selection| selection.0);
+                    let Some((transform, aabb)) = selected.and_then(|e| bounds.get(e).ok()) else {
+                        continue;
+                    };
+
+                    let (scale, _, _) = transform.to_scale_rotation_translation();
+                    let size = (Vec3::from(aabb.half_extents) * scale).length();
+                    let half_fov = match projection {
+                        Projection::Perspective(perspective) => perspective.fov / 2.0,
+                        _ => std::f32::consts::FRAC_PI_4,
+                    };
+
+                    camera.target.focus = transform.transform_point(aabb.center.into());
+                    camera.target.radius =
+                        (size / half_fov.sin()).clamp(camera.min_radius, camera.max_radius);
+                }
+            }
+        }
+    }
+}
+
+fn apply_orbit(time: Res<Time>, mut cameras: Query<(&mut OrbitCamera, &mut Transform)>) {
+    for (mut camera, mut transform) in &mut cameras {
+        let t = 1.0 - camera.damping.powf(time.delta_secs() * 60.0);
+        camera.current = camera.current.lerp(&camera.target, t);
+        *transform = camera.transform();
+    }
+}
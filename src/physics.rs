@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::history::{EditCommand, SceneEdit};
+use crate::scene_description::{MeshDescription, RigidBody, SceneObject};
+use crate::selection::Selection;
+
+/// Simulated time per step, in seconds.
+pub const TIME_STEP: f32 = 1.0 / 60.0;
+
+/// The share of their speed towards each other that bodies keep after bouncing off.
+const RESTITUTION: f32 = 0.3;
+
+/// Slower than this, in meters per second, bodies stop instead of bouncing, so they come to rest.
+const RESTING_SPEED: f32 = 0.5;
+
+/// The share of their speed along each other that bodies lose per step while touching.
+const FRICTION: f32 = 0.1;
+
+/// How often the contacts are resolved per step; more lets stacked bodies settle sooner.
+const ITERATIONS: usize = 4;
+
+/// The world the bodies fall in.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct PhysicsSettings {
+    /// In meters per second squared.
+    pub gravity: Vec3,
+    /// The height of an endless horizontal plane that bodies rest on, `None` for none.
+    pub ground: Option<f32>,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            ground: Some(0.0),
+        }
+    }
+}
+
+/// Controls the simulation from Leptos.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhysicsCommand {
+    Play,
+    Pause,
+    /// Advances the simulation by one `TIME_STEP`, whether it is playing or not.
+    Step,
+    /// Pauses the simulation and puts the bodies back where they were before its first step.
+    Reset,
+    /// Gives an object a body or takes it away, as an `EditCommand`.
+    SetBody {
+        entity: Entity,
+        body: Option<RigidBody>,
+    },
+}
+
+impl PhysicsCommand {
+    /// The part of the command that everyone in a room shares, if any.
+    pub fn playback(self) -> Option<Playback> {
+        match self {
+            PhysicsCommand::Play => Some(Playback::Play),
+            PhysicsCommand::Pause => Some(Playback::Pause),
+            PhysicsCommand::Step => Some(Playback::Step),
+            PhysicsCommand::Reset => Some(Playback::Reset),
+            PhysicsCommand::SetBody { .. } => None,
+        }
+    }
+}
+
+/// The `PhysicsCommand`s that play the simulation, without the ones about single entities.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Playback {
+    Play,
+    Pause,
+    Step,
+    Reset,
+}
+
+impl From<Playback> for PhysicsCommand {
+    fn from(playback: Playback) -> Self {
+        match playback {
+            Playback::Play => PhysicsCommand::Play,
+            Playback::Pause => PhysicsCommand::Pause,
+            Playback::Step => PhysicsCommand::Step,
+            Playback::Reset => PhysicsCommand::Reset,
+        }
+    }
+}
+
+/// How far the simulation is, and the body of the selected object. Written whenever it changes.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhysicsState {
+    pub playing: bool,
+    /// The steps taken since the last reset.
+    pub steps: u64,
+    /// The selected entity and its body, if it is an object.
+    pub selected: Option<(Entity, Option<RigidBody>)>,
+}
+
+/// The progress of the simulation.
+#[derive(Resource, Default, Debug)]
+pub struct PhysicsSimulation {
+    playing: bool,
+    steps: u64,
+    velocities: HashMap<Entity, Vec3>,
+    /// Where the bodies were before they were first simulated.
+    start: HashMap<Entity, Transform>,
+}
+
+impl PhysicsSimulation {
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// The velocity of a dynamic body in meters per second.
+    pub fn velocity(&self, entity: Entity) -> Vec3 {
+        self.velocities.get(&entity).copied().unwrap_or_default()
+    }
+}
+
+/// Simulates the `SceneObject`s with a `RigidBody` while it plays, one `TIME_STEP` per
+/// `FixedUpdate`, controlled by `PhysicsCommand`s and shown as `PhysicsState`.
+///
+/// Bodies only move, they never turn. A step only depends on the bodies and `PhysicsSettings`, so
+/// the same scene always ends up in the same place after the same number of steps; `Step` commands
+/// advance the simulation without depending on the frame rate at all.
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhysicsSettings>()
+            .init_resource::<PhysicsSimulation>()
+            .insert_resource(Time::<Fixed>::from_seconds(TIME_STEP.into()))
+            // Also runs headless, without the `HistoryPlugin` that applies bodies set by commands.
+            .add_event::<EditCommand>()
+            .add_event::<PhysicsCommand>()
+            .add_event::<PhysicsState>()
+            .add_systems(FixedUpdate, play)
+            .add_systems(Update, (apply_physics_commands, report_physics).chain());
+    }
+}
+
+/// The shape a body collides with, centered on its translation.
+///
+/// Spheres stay spheres; every other shape collides as the axis-aligned box around it, which
+/// doesn't change as bodies never turn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Collider {
+    Sphere { radius: f32 },
+    Box { half_size: Vec3 },
+}
+
+impl Collider {
+    /// The collider of `mesh` placed with `transform`.
+    pub fn new(mesh: MeshDescription, transform: &Transform) -> Self {
+        let scale = transform.scale.abs();
+        let half_size = match mesh {
+            MeshDescription::Sphere { radius } => {
+                return Collider::Sphere {
+                    radius: radius * scale.max_element(),
+                };
+            }
+            MeshDescription::Cuboid { size } => Vec3::from(size) / 2.0,
+            MeshDescription::Cylinder { radius, height } => Vec3::new(radius, height / 2.0, radius),
+            MeshDescription::Plane {
+                size: [width, depth],
+            } => Vec3::new(width / 2.0, 0.0, depth / 2.0),
+            MeshDescription::Torus {
+                minor_radius,
+                major_radius,
+            } => {
+                let outer = major_radius + minor_radius;
+                Vec3::new(outer, minor_radius, outer)
+            }
+            MeshDescription::Capsule { radius, length } => {
+                Vec3::new(radius, length / 2.0 + radius, radius)
+            }
+        };
+
+        Collider::Box {
+            half_size: Mat3::from_quat(transform.rotation).abs() * (half_size * scale),
+        }
+    }
+
+    /// Half the size of the box around the collider.
+    fn half_size(self) -> Vec3 {
+        match self {
+            Collider::Sphere { radius } => Vec3::splat(radius),
+            Collider::Box { half_size } => half_size,
+        }
+    }
+}
+
+/// How deep the collider `a` at `a_center` reaches into `b` at `b_center`, with the direction
+/// that pushes `a` out of `b`.
+fn contact(a: Collider, a_center: Vec3, b: Collider, b_center: Vec3) -> Option<(Vec3, f32)> {
+    let offset = a_center - b_center;
+
+    match (a, b) {
+        (Collider::Sphere { radius: a_radius }, Collider::Sphere { radius: b_radius }) => {
+            let distance = offset.length();
+            let depth = a_radius + b_radius - distance;
+            (depth > 0.0).then(|| (offset.try_normalize().unwrap_or(Vec3::Y), depth))
+        }
+        (Collider::Sphere { radius }, Collider::Box { half_size }) => {
+            let closest = offset.clamp(-half_size, half_size);
+            let outside = offset - closest;
+            match outside.try_normalize() {
+                Some(normal) => {
+                    let depth = radius - outside.length();
+                    (depth > 0.0).then_some((normal, depth))
+                }
+                // The center is inside the box, so the sphere leaves it like a box would.
+                None => contact(
+                    Collider::Box {
+                        half_size: Vec3::splat(radius),
+                    },
+                    a_center,
+                    b,
+                    b_center,
+                ),
+            }
+        }
+        (Collider::Box { .. }, Collider::Sphere { .. }) => {
+            contact(b, b_center, a, a_center).map(|(normal, depth)| (-normal, depth))
+        }
+        (Collider::Box { half_size: a_half }, Collider::Box { half_size: b_half }) => {
+            let overlap = a_half + b_half - offset.abs();
+            if overlap.min_element() <= 0.0 {
+                return None;
+            }
+            // Out along the axis with the least overlap.
+            let axis = if overlap.x <= overlap.y && overlap.x <= overlap.z {
+                Vec3::X
+            } else if overlap.y <= overlap.z {
+                Vec3::Y
+            } else {
+                Vec3::Z
+            };
+            Some((axis * offset.dot(axis).signum(), overlap.dot(axis)))
+        }
+    }
+}
+
+/// A body during a step.
+struct Body {
+    entity: Entity,
+    dynamic: bool,
+    collider: Collider,
+    position: Vec3,
+    velocity: Vec3,
+}
+
+/// The change in the velocity of a body moving at `relative` to a surface it touches, which faces
+/// along `normal`.
+fn bounce(relative: Vec3, normal: Vec3) -> Vec3 {
+    let approach = relative.dot(normal);
+    if approach >= 0.0 {
+        return Vec3::ZERO;
+    }
+    let restitution = if -approach > RESTING_SPEED {
+        RESTITUTION
+    } else {
+        0.0
+    };
+    let sliding = relative - normal * approach;
+    -normal * approach * (1.0 + restitution) - sliding * FRICTION
+}
+
+/// Pushes two touching bodies apart along `normal`, which points from `b` to `a`.
+fn resolve(a: &mut Body, b: &mut Body, normal: Vec3, depth: f32) {
+    let (a_share, b_share) = match (a.dynamic, b.dynamic) {
+        (true, true) => (0.5, 0.5),
+        (true, false) => (1.0, 0.0),
+        (false, true) => (0.0, 1.0),
+        (false, false) => return,
+    };
+
+    a.position += normal * depth * a_share;
+    b.position -= normal * depth * b_share;
+
+    let change = bounce(a.velocity - b.velocity, normal);
+    a.velocity += change * a_share;
+    b.velocity -= change * b_share;
+}
+
+/// Advances the bodies by one `TIME_STEP`.
+fn step(
+    simulation: &mut PhysicsSimulation,
+    settings: &PhysicsSettings,
+    objects: &mut Query<(Entity, &SceneObject, &mut Transform)>,
+) {
+    let mut bodies = objects
+        .iter()
+        .filter_map(|(entity, object, transform)| {
+            let body = object.body?;
+            simulation.start.entry(entity).or_insert(*transform);
+            Some((
+                object.id,
+                Body {
+                    entity,
+                    dynamic: body == RigidBody::Dynamic,
+                    collider: Collider::new(object.mesh, transform),
+                    position: transform.translation,
+                    velocity: match body {
+                        RigidBody::Static => Vec3::ZERO,
+                        RigidBody::Dynamic => simulation.velocity(entity),
+                    },
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+    // Always resolved in the same order, whatever order the query has.
+    bodies.sort_by_key(|(id, _)| *id);
+    let mut bodies = bodies.into_iter().map(|(_, body)| body).collect::<Vec<_>>();
+
+    for body in bodies.iter_mut().filter(|body| body.dynamic) {
+        body.velocity += settings.gravity * TIME_STEP;
+        body.position += body.velocity * TIME_STEP;
+    }
+
+    for _ in 0..ITERATIONS {
+        for index in 0..bodies.len() {
+            let (body, others) = bodies[index..]
+                .split_first_mut()
+                .expect("index is in bounds");
+
+            for other in others {
+                if let Some((normal, depth)) =
+                    contact(body.collider, body.position, other.collider, other.position)
+                {
+                    resolve(body, other, normal, depth);
+                }
+            }
+
+            if let (true, Some(ground)) = (body.dynamic, settings.ground) {
+                let depth = ground - (body.position.y - body.collider.half_size().y);
+                if depth > 0.0 {
+                    body.position.y += depth;
+                    body.velocity += bounce(body.velocity, Vec3::Y);
+                }
+            }
+        }
+    }
+
+    simulation.velocities.clear();
+    for body in bodies {
+        if let Ok((_, _, mut transform)) = objects.get_mut(body.entity) {
+            transform.translation = body.position;
+        }
+        if body.dynamic {
+            simulation.velocities.insert(body.entity, body.velocity);
+        }
+    }
+    simulation.steps += 1;
+}
+
+fn play(
+    mut simulation: ResMut<PhysicsSimulation>,
+    settings: Res<PhysicsSettings>,
+    mut objects: Query<(Entity, &SceneObject, &mut Transform)>,
+) {
+    if simulation.playing {
+        step(&mut simulation, &settings, &mut objects);
+    }
+}
+
+fn apply_physics_commands(
+    mut commands: EventReader<PhysicsCommand>,
+    mut simulation: ResMut<PhysicsSimulation>,
+    settings: Res<PhysicsSettings>,
+    mut objects: Query<(Entity, &SceneObject, &mut Transform)>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    for command in commands.read() {
+        match *command {
+            PhysicsCommand::Play => simulation.playing = true,
+            PhysicsCommand::Pause => simulation.playing = false,
+            PhysicsCommand::Step => step(&mut simulation, &settings, &mut objects),
+            PhysicsCommand::Reset => {
+                for (entity, start) in simulation.start.drain() {
+                    if let Ok((_, _, mut transform)) = objects.get_mut(entity) {
+                        *transform = start;
+                    }
+                }
+                simulation.velocities.clear();
+                simulation.steps = 0;
+                simulation.playing = false;
+            }
+            PhysicsCommand::SetBody { entity, body } => {
+                if objects.contains(entity) {
+                    edits.write(EditCommand {
+                        entity,
+                        edit: SceneEdit::Body(body),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn report_physics(
+    simulation: Res<PhysicsSimulation>,
+    selection: Option<Res<Selection>>,
+    objects: Query<&SceneObject>,
+    mut last: Local<Option<PhysicsState>>,
+    mut states: EventWriter<PhysicsState>,
+) {
+    let state = PhysicsState {
+        playing: simulation.playing,
+        steps: simulation.steps,
+        selected: selection
+            .and_then(|selection| selection.0)
+            .and_then(|entity| Some((entity, objects.get(entity).ok()?.body))),
+    };
+
+    if *last != Some(state) {
+        *last = Some(state);
+        states.write(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{PhysicsCommand, PhysicsPlugin, PhysicsSimulation, TIME_STEP};
+    use crate::scene_description::{MeshDescription, RigidBody, SceneObject};
+
+    const GRAVITY: f32 = 9.81;
+
+    const BALL: MeshDescription = MeshDescription::Sphere { radius: 0.5 };
+
+    /// Where `bodies` are after `steps` steps, in the order given.
+    fn simulate(bodies: &[(MeshDescription, Vec3, RigidBody)], steps: u64) -> Vec<Vec3> {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PhysicsPlugin));
+
+        let entities = bodies
+            .iter()
+            .enumerate()
+            .map(|(id, &(mesh, translation, body))| {
+                app.world_mut()
+                    .spawn((
+                        SceneObject {
+                            id: id as u64,
+                            mesh,
+                            roles: Vec::new(),
+                            body: Some(body),
+                        },
+                        Transform::from_translation(translation),
+                    ))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+
+        for _ in 0..steps {
+            app.world_mut().send_event(PhysicsCommand::Step);
+            app.update();
+        }
+        assert_eq!(app.world().resource::<PhysicsSimulation>().steps(), steps);
+
+        entities
+            .into_iter()
+            .map(|entity| app.world().get::<Transform>(entity).unwrap().translation)
+            .collect()
+    }
+
+    #[test]
+    fn dynamic_bodies_fall_with_gravity() {
+        let steps = 30;
+        let [ball] = simulate(
+            &[(BALL, Vec3::new(1.0, 10.0, 2.0), RigidBody::Dynamic)],
+            steps,
+        )[..] else {
+            unreachable!();
+        };
+
+        // The velocity changes before the position in every step.
+        let fallen = GRAVITY * TIME_STEP * TIME_STEP * (steps * (steps + 1) / 2) as f32;
+        assert!(ball.abs_diff_eq(Vec3::new(1.0, 10.0 - fallen, 2.0), 1e-5));
+    }
+
+    #[test]
+    fn dynamic_bodies_come_to_rest_on_static_bodies_and_the_ground() {
+        let translations = simulate(
+            &[
+                (
+                    MeshDescription::Cuboid {
+                        size: [2.0, 2.0, 2.0],
+                    },
+                    Vec3::new(0.0, 1.0, 0.0),
+                    RigidBody::Static,
+                ),
+                (BALL, Vec3::new(0.0, 4.0, 0.0), RigidBody::Dynamic),
+                (BALL, Vec3::new(5.0, 3.0, 0.0), RigidBody::Dynamic),
+            ],
+            300,
+        );
+
+        assert_eq!(
+            translations,
+            [
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, 2.5, 0.0),
+                Vec3::new(5.0, 0.5, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn simulations_are_deterministic() {
+        let bodies = [
+            (
+                MeshDescription::Cuboid {
+                    size: [1.0, 1.0, 1.0],
+                },
+                Vec3::new(0.0, 2.0, 0.0),
+                RigidBody::Dynamic,
+            ),
+            (BALL, Vec3::new(0.3, 4.0, -0.2), RigidBody::Dynamic),
+            (BALL, Vec3::new(-0.4, 6.0, 0.1), RigidBody::Dynamic),
+        ];
+
+        for steps in [1, 45, 240] {
+            assert_eq!(simulate(&bodies, steps), simulate(&bodies, steps));
+        }
+    }
+}
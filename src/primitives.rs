@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+
+use crate::history::{EditCommand, HistorySystems, SceneChild, SceneEdit};
+use crate::keyboard_focus::canvas_focused;
+use crate::orbit_camera::OrbitCamera;
+use crate::scene_description::{
+    MaterialDescription, MeshDescription, ObjectDescription, ObjectIds, SceneObject,
+    TransformDescription,
+};
+use crate::selection::Selection;
+
+/// The shapes that can be added to the scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Cube,
+    Sphere,
+    Cylinder,
+    Plane,
+    Torus,
+    Capsule,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 6] = [
+        Primitive::Cube,
+        Primitive::Sphere,
+        Primitive::Cylinder,
+        Primitive::Plane,
+        Primitive::Torus,
+        Primitive::Capsule,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Primitive::Cube => "Cube",
+            Primitive::Sphere => "Sphere",
+            Primitive::Cylinder => "Cylinder",
+            Primitive::Plane => "Plane",
+            Primitive::Torus => "Torus",
+            Primitive::Capsule => "Capsule",
+        }
+    }
+
+    /// The mesh of a new object, about one unit across.
+    pub fn mesh(self) -> MeshDescription {
+        match self {
+            Primitive::Cube => MeshDescription::Cuboid {
+                size: [1.0, 1.0, 1.0],
+            },
+            Primitive::Sphere => MeshDescription::Sphere { radius: 0.5 },
+            Primitive::Cylinder => MeshDescription::Cylinder {
+                radius: 0.5,
+                height: 1.0,
+            },
+            Primitive::Plane => MeshDescription::Plane { size: [2.0, 2.0] },
+            Primitive::Torus => MeshDescription::Torus {
+                minor_radius: 0.25,
+                major_radius: 0.5,
+            },
+            Primitive::Capsule => MeshDescription::Capsule {
+                radius: 0.25,
+                length: 0.5,
+            },
+        }
+    }
+}
+
+/// How far in front of the camera new objects are placed.
+const SPAWN_DISTANCE: f32 = 3.0;
+
+/// Adds objects to the scene and removes them, sent from Leptos.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectCommand {
+    /// Adds a primitive in front of the camera and selects it.
+    Spawn(Primitive),
+    /// Removes the selected object.
+    DeleteSelected,
+}
+
+/// Handles `ObjectCommand`s; Delete removes the selected object while the canvas has focus.
+///
+/// New objects get their id from `ObjectIds`, so they are shared with the room like any other.
+/// Adding and removing objects are `EditCommand`s, so both can be undone.
+pub struct PrimitivesPlugin;
+
+impl Plugin for PrimitivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ObjectCommand>()
+            .add_systems(Update, delete_with_key.run_if(canvas_focused))
+            // After `Update`, so the selection isn't cleared before the new object is spawned.
+            .add_systems(
+                PostUpdate,
+                (spawn_primitives, delete_selected).before(HistorySystems),
+            );
+    }
+}
+
+fn spawn_primitives(
+    mut commands: Commands,
+    mut events: EventReader<ObjectCommand>,
+    camera: Single<(&OrbitCamera, &ChildOf)>,
+    mut ids: ResMut<ObjectIds>,
+    mut selection: ResMut<Selection>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    // The camera of a described scene is one of its children.
+    let (camera, ChildOf(scene)) = *camera;
+    // Not at the focus, which mostly lies inside the object that is looked at.
+    let forward = (camera.focus() - camera.eye()).normalize();
+    let translation = camera.eye() + forward * SPAWN_DISTANCE;
+
+    for event in events.read() {
+        let ObjectCommand::Spawn(primitive) = *event else {
+            continue;
+        };
+
+        let id = ids.next_id();
+        let object = ObjectDescription {
+            name: Some(format!("{} {}", primitive.label(), id as u32)),
+            mesh: primitive.mesh(),
+            material: MaterialDescription::default(),
+            transform: TransformDescription {
+                translation: translation.into(),
+                ..default()
+            },
+            roles: Vec::new(),
+            body: None,
+        };
+
+        let entity = commands.spawn_empty().id();
+        edits.write(EditCommand {
+            entity,
+            edit: SceneEdit::Child(Some((
+                *scene,
+                SceneChild::Object {
+                    id,
+                    description: Box::new(object),
+                },
+            ))),
+        });
+        selection.0 = Some(entity);
+    }
+}
+
+fn delete_selected(
+    mut events: EventReader<ObjectCommand>,
+    objects: Query<(), With<SceneObject>>,
+    selection: Res<Selection>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    for event in events.read() {
+        if *event != ObjectCommand::DeleteSelected {
+            continue;
+        }
+        if let Some(entity) = selection.0.filter(|entity| objects.contains(*entity)) {
+            edits.write(EditCommand {
+                entity,
+                edit: SceneEdit::Child(None),
+            });
+        }
+    }
+}
+
+fn delete_with_key(keys: Res<ButtonInput<KeyCode>>, mut commands: EventWriter<ObjectCommand>) {
+    if keys.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        commands.write(ObjectCommand::DeleteSelected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{ObjectCommand, Primitive, PrimitivesPlugin, SPAWN_DISTANCE};
+    use crate::history::{HistoryCommand, HistoryPlugin};
+    use crate::orbit_camera::OrbitCamera;
+    use crate::scene_description::{SceneDescription, SceneObject};
+    use crate::selection::Selection;
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    #[test]
+    fn primitives_are_placed_in_front_of_the_camera_until_undone() {
+        let scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        let mut app = headless_app(scene);
+        app.init_resource::<Selection>()
+            .add_plugins((HistoryPlugin, PrimitivesPlugin));
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        app.world_mut()
+            .send_event(ObjectCommand::Spawn(Primitive::Sphere));
+        app.update();
+
+        let camera = app
+            .world_mut()
+            .query::<&OrbitCamera>()
+            .single(app.world())
+            .unwrap();
+        let (eye, focus) = (camera.eye(), camera.focus());
+        let selected = app.world().resource::<Selection>().0.unwrap();
+        assert!(app.world().get::<SceneObject>(selected).is_some());
+        let translation = app.world().get::<Transform>(selected).unwrap().translation;
+        assert!(translation.distance(focus) > 1.0);
+        assert!(translation.distance(eye + (focus - eye).normalize() * SPAWN_DISTANCE) < 1e-4);
+
+        app.world_mut().send_event(HistoryCommand::Undo);
+        app.update();
+        assert!(app.world().get_entity(selected).is_err());
+    }
+}
@@ -0,0 +1,1168 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::image::ImageLoaderSettings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+use crate::cube_color::Cube;
+use crate::orbit_camera::OrbitCamera;
+use crate::scene_text::SceneTextAnchor;
+use crate::selection::Selectable;
+
+/// A scene as it is stored in `.scene.ron` and `.scene.json` files.
+///
+/// Colors are sRGB(A) components between 0 and 1, rotations are Euler angles in degrees applied
+/// in X, Y, Z order, and all other values are in meters.
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+    #[serde(default)]
+    pub lights: Vec<LightDescription>,
+    #[serde(default)]
+    pub environment: EnvironmentDescription,
+    pub camera: CameraDescription,
+}
+
+/// A mesh in the scene.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ObjectDescription {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub mesh: MeshDescription,
+    #[serde(default)]
+    pub material: MaterialDescription,
+    #[serde(default)]
+    pub transform: TransformDescription,
+    #[serde(default)]
+    pub roles: Vec<ObjectRole>,
+    #[serde(default)]
+    pub body: Option<RigidBody>,
+}
+
+/// What an object does in the page besides being shown and selectable.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectRole {
+    /// Takes its color from the color picker.
+    Cube,
+    /// Carries the text typed into the page.
+    TextAnchor,
+}
+
+/// How an object takes part in the physics simulation. Objects without a body are left alone.
+#[derive(Serialize, Deserialize, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RigidBody {
+    /// Never moves, but dynamic bodies collide with it.
+    Static,
+    /// Falls, and is pushed around by the bodies it collides with.
+    Dynamic,
+}
+
+/// The primitive shape of an object.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MeshDescription {
+    Cuboid {
+        size: [f32; 3],
+    },
+    Sphere {
+        radius: f32,
+    },
+    Cylinder {
+        radius: f32,
+        height: f32,
+    },
+    Plane {
+        size: [f32; 2],
+    },
+    Torus {
+        minor_radius: f32,
+        major_radius: f32,
+    },
+    Capsule {
+        radius: f32,
+        length: f32,
+    },
+}
+
+impl From<MeshDescription> for Mesh {
+    fn from(mesh: MeshDescription) -> Self {
+        match mesh {
+            MeshDescription::Cuboid { size } => Cuboid::from_size(size.into()).into(),
+            MeshDescription::Sphere { radius } => Sphere::new(radius).into(),
+            MeshDescription::Cylinder { radius, height } => Cylinder::new(radius, height).into(),
+            MeshDescription::Plane { size } => {
+                Plane3d::default().mesh().size(size[0], size[1]).into()
+            }
+            MeshDescription::Torus {
+                minor_radius,
+                major_radius,
+            } => Torus::new(minor_radius, major_radius).into(),
+            MeshDescription::Capsule { radius, length } => Capsule3d::new(radius, length).into(),
+        }
+    }
+}
+
+/// The surface of an object.
+///
+/// Objects whose materials have the same `name` share one material, so changing it changes all of
+/// them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MaterialDescription {
+    pub name: Option<String>,
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub perceptual_roughness: f32,
+    /// Specular intensity for non-metals, between 0 and 1.
+    pub reflectance: f32,
+    pub emissive: [f32; 3],
+    /// How alpha is applied. `None` blends translucent colors and is opaque otherwise.
+    pub alpha_mode: Option<AlphaModeDescription>,
+    pub textures: MaterialTextures,
+}
+
+impl Default for MaterialDescription {
+    fn default() -> Self {
+        Self {
+            name: None,
+            base_color: [1.0, 1.0, 1.0, 1.0],
+            metallic: 0.0,
+            perceptual_roughness: 0.5,
+            reflectance: 0.5,
+            emissive: [0.0, 0.0, 0.0],
+            alpha_mode: None,
+            textures: MaterialTextures::default(),
+        }
+    }
+}
+
+/// How the alpha of a material is applied, see `AlphaMode`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AlphaModeDescription {
+    Opaque,
+    /// Fully transparent below the cutoff and opaque otherwise.
+    Mask(f32),
+    Blend,
+    Premultiplied,
+    Add,
+    Multiply,
+}
+
+impl From<AlphaModeDescription> for AlphaMode {
+    fn from(alpha_mode: AlphaModeDescription) -> Self {
+        match alpha_mode {
+            AlphaModeDescription::Opaque => AlphaMode::Opaque,
+            AlphaModeDescription::Mask(cutoff) => AlphaMode::Mask(cutoff),
+            AlphaModeDescription::Blend => AlphaMode::Blend,
+            AlphaModeDescription::Premultiplied => AlphaMode::Premultiplied,
+            AlphaModeDescription::Add => AlphaMode::Add,
+            AlphaModeDescription::Multiply => AlphaMode::Multiply,
+        }
+    }
+}
+
+impl From<AlphaMode> for AlphaModeDescription {
+    fn from(alpha_mode: AlphaMode) -> Self {
+        match alpha_mode {
+            AlphaMode::Opaque => AlphaModeDescription::Opaque,
+            AlphaMode::Mask(cutoff) => AlphaModeDescription::Mask(cutoff),
+            AlphaMode::Premultiplied => AlphaModeDescription::Premultiplied,
+            AlphaMode::Add => AlphaModeDescription::Add,
+            AlphaMode::Multiply => AlphaModeDescription::Multiply,
+            AlphaMode::Blend | AlphaMode::AlphaToCoverage => AlphaModeDescription::Blend,
+        }
+    }
+}
+
+/// Asset paths of the images a material is textured with, like `textures/checker.png`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct MaterialTextures {
+    pub base_color: Option<String>,
+    pub normal_map: Option<String>,
+    pub metallic_roughness: Option<String>,
+    pub emissive: Option<String>,
+    pub occlusion: Option<String>,
+}
+
+/// The textures of a `StandardMaterial` that can be described.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureSlot {
+    BaseColor,
+    NormalMap,
+    MetallicRoughness,
+    Emissive,
+    Occlusion,
+}
+
+impl TextureSlot {
+    pub const ALL: [TextureSlot; 5] = [
+        TextureSlot::BaseColor,
+        TextureSlot::NormalMap,
+        TextureSlot::MetallicRoughness,
+        TextureSlot::Emissive,
+        TextureSlot::Occlusion,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextureSlot::BaseColor => "Base color map",
+            TextureSlot::NormalMap => "Normal map",
+            TextureSlot::MetallicRoughness => "Metallic / roughness map",
+            TextureSlot::Emissive => "Emissive map",
+            TextureSlot::Occlusion => "Occlusion map",
+        }
+    }
+
+    /// Colors are stored in sRGB, while the other textures hold data.
+    pub fn is_srgb(self) -> bool {
+        matches!(self, TextureSlot::BaseColor | TextureSlot::Emissive)
+    }
+
+    fn texture(self, material: &StandardMaterial) -> &Option<Handle<Image>> {
+        match self {
+            TextureSlot::BaseColor => &material.base_color_texture,
+            TextureSlot::NormalMap => &material.normal_map_texture,
+            TextureSlot::MetallicRoughness => &material.metallic_roughness_texture,
+            TextureSlot::Emissive => &material.emissive_texture,
+            TextureSlot::Occlusion => &material.occlusion_texture,
+        }
+    }
+
+    fn texture_mut(self, material: &mut StandardMaterial) -> &mut Option<Handle<Image>> {
+        match self {
+            TextureSlot::BaseColor => &mut material.base_color_texture,
+            TextureSlot::NormalMap => &mut material.normal_map_texture,
+            TextureSlot::MetallicRoughness => &mut material.metallic_roughness_texture,
+            TextureSlot::Emissive => &mut material.emissive_texture,
+            TextureSlot::Occlusion => &mut material.occlusion_texture,
+        }
+    }
+}
+
+impl MaterialTextures {
+    pub fn get(&self, slot: TextureSlot) -> &Option<String> {
+        match slot {
+            TextureSlot::BaseColor => &self.base_color,
+            TextureSlot::NormalMap => &self.normal_map,
+            TextureSlot::MetallicRoughness => &self.metallic_roughness,
+            TextureSlot::Emissive => &self.emissive,
+            TextureSlot::Occlusion => &self.occlusion,
+        }
+    }
+
+    pub fn get_mut(&mut self, slot: TextureSlot) -> &mut Option<String> {
+        match slot {
+            TextureSlot::BaseColor => &mut self.base_color,
+            TextureSlot::NormalMap => &mut self.normal_map,
+            TextureSlot::MetallicRoughness => &mut self.metallic_roughness,
+            TextureSlot::Emissive => &mut self.emissive,
+            TextureSlot::Occlusion => &mut self.occlusion,
+        }
+    }
+}
+
+impl MaterialDescription {
+    /// Sets the described properties of `material`, leaving the others as they are.
+    ///
+    /// Textures are loaded with `asset_server`, unless `material` already has the one at the same
+    /// path. The name is kept by the `MaterialLibrary` instead.
+    pub fn apply(&self, material: &mut StandardMaterial, asset_server: &AssetServer) {
+        let [red, green, blue, alpha] = self.base_color;
+        let [emissive_red, emissive_green, emissive_blue] = self.emissive;
+
+        material.base_color = Color::srgba(red, green, blue, alpha);
+        material.metallic = self.metallic;
+        material.perceptual_roughness = self.perceptual_roughness;
+        material.reflectance = self.reflectance;
+        material.emissive = Color::srgb(emissive_red, emissive_green, emissive_blue).into();
+        material.alpha_mode = match self.alpha_mode {
+            Some(alpha_mode) => alpha_mode.into(),
+            None if alpha < 1.0 => AlphaMode::Blend,
+            None => AlphaMode::Opaque,
+        };
+
+        for slot in TextureSlot::ALL {
+            let path = self.textures.get(slot);
+            let texture = slot.texture_mut(material);
+            if texture_path(texture) == *path {
+                continue;
+            }
+
+            *texture = path.as_ref().map(|path| {
+                let is_srgb = slot.is_srgb();
+                asset_server.load_with_settings(
+                    path.clone(),
+                    move |settings: &mut ImageLoaderSettings| {
+                        settings.is_srgb = is_srgb;
+                    },
+                )
+            });
+        }
+    }
+}
+
+fn texture_path(texture: &Option<Handle<Image>>) -> Option<String> {
+    texture.as_ref()?.path().map(|path| path.to_string())
+}
+
+/// Placement of an object or light.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct TransformDescription {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl Default for TransformDescription {
+    fn default() -> Self {
+        Self {
+            translation: [0.0, 0.0, 0.0],
+            rotation: [0.0, 0.0, 0.0],
+            scale: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+/// Describes `material` without a name, which only the `MaterialLibrary` knows.
+impl From<&StandardMaterial> for MaterialDescription {
+    fn from(material: &StandardMaterial) -> Self {
+        let base_color = material.base_color.to_srgba();
+        let emissive = Color::from(material.emissive).to_srgba();
+
+        let mut textures = MaterialTextures::default();
+        for slot in TextureSlot::ALL {
+            *textures.get_mut(slot) = texture_path(slot.texture(material));
+        }
+
+        Self {
+            name: None,
+            base_color: base_color.to_f32_array(),
+            metallic: material.metallic,
+            perceptual_roughness: material.perceptual_roughness,
+            reflectance: material.reflectance,
+            emissive: emissive.to_f32_array_no_alpha(),
+            alpha_mode: Some(material.alpha_mode.into()),
+            textures,
+        }
+    }
+}
+
+impl From<TransformDescription> for Transform {
+    fn from(transform: TransformDescription) -> Self {
+        let [x, y, z] = transform.rotation.map(f32::to_radians);
+
+        Transform {
+            translation: transform.translation.into(),
+            rotation: Quat::from_euler(EulerRot::XYZ, x, y, z),
+            scale: transform.scale.into(),
+        }
+    }
+}
+
+impl From<&Transform> for TransformDescription {
+    fn from(transform: &Transform) -> Self {
+        let (x, y, z) = transform.rotation.to_euler(EulerRot::XYZ);
+
+        Self {
+            translation: transform.translation.into(),
+            rotation: [x, y, z].map(f32::to_degrees),
+            scale: transform.scale.into(),
+        }
+    }
+}
+
+/// A light source. Directional and spot lights shine along their local -Z axis.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum LightDescription {
+    Point {
+        color: [f32; 3],
+        /// Luminous power in lumens.
+        intensity: f32,
+        range: f32,
+        #[serde(default)]
+        shadows: bool,
+        #[serde(default)]
+        transform: TransformDescription,
+    },
+    Directional {
+        color: [f32; 3],
+        /// Illuminance in lux.
+        illuminance: f32,
+        #[serde(default)]
+        shadows: bool,
+        #[serde(default)]
+        transform: TransformDescription,
+    },
+    Spot {
+        color: [f32; 3],
+        /// Luminous power in lumens.
+        intensity: f32,
+        range: f32,
+        /// Angles in degrees.
+        inner_angle: f32,
+        outer_angle: f32,
+        #[serde(default)]
+        shadows: bool,
+        #[serde(default)]
+        transform: TransformDescription,
+    },
+}
+
+impl LightDescription {
+    pub fn color_mut(&mut self) -> &mut [f32; 3] {
+        match self {
+            LightDescription::Point { color, .. }
+            | LightDescription::Directional { color, .. }
+            | LightDescription::Spot { color, .. } => color,
+        }
+    }
+
+    pub fn shadows_mut(&mut self) -> &mut bool {
+        match self {
+            LightDescription::Point { shadows, .. }
+            | LightDescription::Directional { shadows, .. }
+            | LightDescription::Spot { shadows, .. } => shadows,
+        }
+    }
+}
+
+/// The light that doesn't come from the lights of the scene, and how shadows are drawn.
+#[derive(Serialize, Deserialize, Reflect, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct EnvironmentDescription {
+    /// Light reaching every surface from all directions, `None` for none at all.
+    pub ambient: Option<AmbientDescription>,
+    /// A sky around the scene that lights it and is reflected by it.
+    pub environment_map: Option<EnvironmentMapDescription>,
+    pub shadow_quality: ShadowQuality,
+}
+
+impl Default for EnvironmentDescription {
+    fn default() -> Self {
+        Self {
+            ambient: Some(AmbientDescription::default()),
+            environment_map: None,
+            shadow_quality: ShadowQuality::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Reflect, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct AmbientDescription {
+    pub color: [f32; 3],
+    /// Luminance in candela per square meter.
+    pub brightness: f32,
+}
+
+impl Default for AmbientDescription {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 1.0, 1.0],
+            brightness: 80.0,
+        }
+    }
+}
+
+/// A sky that fades from `ground` below the horizon to `horizon` and up to `sky`.
+#[derive(Serialize, Deserialize, Reflect, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct EnvironmentMapDescription {
+    pub sky: [f32; 3],
+    pub horizon: [f32; 3],
+    pub ground: [f32; 3],
+    /// Luminance of white in candela per square meter.
+    pub intensity: f32,
+}
+
+impl Default for EnvironmentMapDescription {
+    fn default() -> Self {
+        Self {
+            sky: [0.35, 0.55, 0.9],
+            horizon: [0.9, 0.9, 0.85],
+            ground: [0.3, 0.27, 0.25],
+            intensity: 500.0,
+        }
+    }
+}
+
+/// How sharp shadows are, against how long they take to draw.
+#[derive(Serialize, Deserialize, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadowQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    pub const ALL: [ShadowQuality; 3] = [
+        ShadowQuality::Low,
+        ShadowQuality::Medium,
+        ShadowQuality::High,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShadowQuality::Low => "Low",
+            ShadowQuality::Medium => "Medium",
+            ShadowQuality::High => "High",
+        }
+    }
+}
+
+/// Where the orbit camera starts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CameraDescription {
+    pub eye: [f32; 3],
+    pub focus: [f32; 3],
+}
+
+#[derive(Debug, Error)]
+pub enum SceneDescriptionError {
+    #[error("could not read scene: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid RON scene: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid JSON scene: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl SceneDescription {
+    pub fn from_ron(text: &str) -> Result<Self, SceneDescriptionError> {
+        Ok(ron::from_str(text)?)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, SceneDescriptionError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("scene descriptions always serialize")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("scene descriptions always serialize")
+    }
+}
+
+/// Loads `.scene.ron` and `.scene.json` files as `SceneDescription`s.
+#[derive(Default)]
+struct SceneDescriptionLoader;
+
+impl AssetLoader for SceneDescriptionLoader {
+    type Asset = SceneDescription;
+    type Settings = ();
+    type Error = SceneDescriptionError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SceneDescription, SceneDescriptionError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).await?;
+
+        if load_context
+            .path()
+            .extension()
+            .is_some_and(|ext| ext == "json")
+        {
+            SceneDescription::from_json(&text)
+        } else {
+            SceneDescription::from_ron(&text)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scene.ron", "scene.json"]
+    }
+}
+
+/// Requests sent from Leptos to the scene.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub enum SceneCommand {
+    /// Reply with a `SceneSnapshot` of the scene as it is now.
+    Snapshot,
+    /// Replace the scene with this one.
+    Load(SceneDescription),
+}
+
+/// The scene as it was when `SceneCommand::Snapshot` was sent.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct SceneSnapshot(pub SceneDescription);
+
+/// Spawns the scene with this description as children of the entity.
+///
+/// The children are spawned again whenever the description changes. Objects added to the scene
+/// later have to be children of this entity as well to end up in a `SceneSnapshot`.
+///
+/// The entity is the root of the scene's hierarchy, so it needs a `Transform` of its own for the
+/// transforms of its children to reach their `GlobalTransform`s.
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct DescribedScene(pub Handle<SceneDescription>);
+
+/// The environment of a spawned `DescribedScene`, as it is now.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct SceneEnvironment(pub EnvironmentDescription);
+
+/// Marks a `DescribedScene` whose children are up to date.
+#[derive(Component)]
+pub(crate) struct DescribedSceneSpawned;
+
+/// The ids to give the objects of a `DescribedScene` whenever it is spawned, in the order of its
+/// description, rather than their positions in it. Loading another scene removes them.
+///
+/// Scenes read back from someone else keep the ids of the objects that were added to them later.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct DescribedObjectIds(pub Vec<u64>);
+
+/// The parts of an object that can't be read back from its other components.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct SceneObject {
+    /// Identifies the object to everyone editing the scene. Objects spawned from the description
+    /// are numbered by their position in it unless the scene has `DescribedObjectIds`, later ones
+    /// get their id from `ObjectIds`.
+    pub id: u64,
+    pub mesh: MeshDescription,
+    pub roles: Vec<ObjectRole>,
+    pub body: Option<RigidBody>,
+}
+
+/// Hands out ids for objects added to a scene after it was spawned.
+///
+/// The ids start at `(client + 1) << 32`, so they never collide with the ids of described objects
+/// or with objects added by other clients.
+#[derive(Resource, Default, Debug)]
+pub struct ObjectIds {
+    pub client: u32,
+    next: u32,
+}
+
+impl ObjectIds {
+    pub fn next_id(&mut self) -> u64 {
+        self.next += 1;
+        ((u64::from(self.client) + 1) << 32) | u64::from(self.next)
+    }
+}
+
+/// The named materials of the scene, which objects share by naming them in their description.
+///
+/// It is cleared whenever a `DescribedScene` is spawned again, so the names always refer to
+/// materials of the current scene.
+#[derive(Resource, Default, Debug)]
+pub struct MaterialLibrary {
+    materials: BTreeMap<String, Handle<StandardMaterial>>,
+}
+
+impl MaterialLibrary {
+    pub fn get(&self, name: &str) -> Option<&Handle<StandardMaterial>> {
+        self.materials.get(name)
+    }
+
+    /// The name of the material with this handle, if it has one.
+    pub fn name_of(&self, material: &Handle<StandardMaterial>) -> Option<&str> {
+        self.materials
+            .iter()
+            .find(|(_, handle)| handle.id() == material.id())
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.materials.keys().map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: String, material: Handle<StandardMaterial>) {
+        self.materials.insert(name, material);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Handle<StandardMaterial>> {
+        self.materials.remove(name)
+    }
+
+    /// Gives the material named `from` the name `to`, unless that one is taken.
+    pub fn rename(&mut self, from: &str, to: String) -> bool {
+        if self.materials.contains_key(&to) {
+            return false;
+        }
+        let Some(material) = self.materials.remove(from) else {
+            return false;
+        };
+
+        self.materials.insert(to, material);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.materials.clear();
+    }
+}
+
+/// Loads `SceneDescription` assets, spawns `DescribedScene`s and handles `SceneCommand`s.
+pub struct SceneDescriptionPlugin;
+
+impl Plugin for SceneDescriptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SceneDescription>()
+            .init_asset_loader::<SceneDescriptionLoader>()
+            .init_resource::<ObjectIds>()
+            .init_resource::<MaterialLibrary>()
+            .add_event::<SceneCommand>()
+            .add_event::<SceneSnapshot>()
+            .add_systems(
+                Update,
+                (
+                    snapshot_scene,
+                    load_scene,
+                    respawn_changed_scenes,
+                    spawn_described_scenes,
+                )
+                    .chain(),
+            );
+    }
+}
+
+type ObjectParts = (
+    &'static SceneObject,
+    Option<&'static Name>,
+    &'static Transform,
+    &'static MeshMaterial3d<StandardMaterial>,
+);
+
+type AnyLight = AnyOf<(
+    &'static PointLight,
+    &'static DirectionalLight,
+    &'static SpotLight,
+)>;
+
+/// Reads the spawned `DescribedScene` back into a `SceneDescription`.
+#[derive(SystemParam)]
+pub struct SceneReader<'w, 's> {
+    descriptions: Res<'w, Assets<SceneDescription>>,
+    materials: Res<'w, Assets<StandardMaterial>>,
+    library: Res<'w, MaterialLibrary>,
+    scenes: Query<
+        'w,
+        's,
+        (
+            &'static DescribedScene,
+            &'static Children,
+            Option<&'static SceneEnvironment>,
+        ),
+    >,
+    objects: Query<'w, 's, ObjectParts>,
+    lights: Query<'w, 's, (&'static Transform, AnyLight)>,
+    cameras: Query<'w, 's, &'static OrbitCamera>,
+}
+
+impl SceneReader<'_, '_> {
+    /// The description of a single object.
+    pub fn object(&self, entity: Entity) -> Option<ObjectDescription> {
+        let (object, name, transform, material) = self.objects.get(entity).ok()?;
+
+        Some(ObjectDescription {
+            name: name.map(|name| name.to_string()),
+            mesh: object.mesh,
+            material: self.material(material).unwrap_or_default(),
+            transform: transform.into(),
+            roles: object.roles.clone(),
+            body: object.body,
+        })
+    }
+
+    /// The description of a material, named after its entry in the `MaterialLibrary`.
+    pub fn material(&self, material: &Handle<StandardMaterial>) -> Option<MaterialDescription> {
+        Some(MaterialDescription {
+            name: self.library.name_of(material).map(str::to_string),
+            ..MaterialDescription::from(self.materials.get(material)?)
+        })
+    }
+
+    /// The description of a single light.
+    pub fn light(&self, entity: Entity) -> Option<LightDescription> {
+        let (transform, light) = self.lights.get(entity).ok()?;
+        Some(describe_light(transform, light))
+    }
+
+    /// The first spawned scene, with objects and lights in the order of its children.
+    pub fn read(&self) -> Option<SceneDescription> {
+        let (scene, children, environment) = self.scenes.iter().next()?;
+        let mut description = self.descriptions.get(&scene.0)?.clone();
+        description.objects.clear();
+        description.lights.clear();
+        if let Some(SceneEnvironment(environment)) = environment {
+            description.environment = environment.clone();
+        }
+
+        for child in children {
+            if let Some(object) = self.object(*child) {
+                description.objects.push(object);
+            } else if let Some(light) = self.light(*child) {
+                description.lights.push(light);
+            } else if let Ok(camera) = self.cameras.get(*child) {
+                description.camera = CameraDescription {
+                    eye: camera.eye().into(),
+                    focus: camera.focus().into(),
+                };
+            }
+        }
+
+        Some(description)
+    }
+
+    /// The ids of the objects of the scene that `read` describes, in the same order.
+    pub fn object_ids(&self) -> Vec<u64> {
+        let Some((_, children, _)) = self.scenes.iter().next() else {
+            return Vec::new();
+        };
+
+        children
+            .iter()
+            .filter_map(|child| Some(self.objects.get(child).ok()?.0.id))
+            .collect()
+    }
+}
+
+pub(crate) fn describe_light(
+    transform: &Transform,
+    light: (
+        Option<&PointLight>,
+        Option<&DirectionalLight>,
+        Option<&SpotLight>,
+    ),
+) -> LightDescription {
+    let srgb = |color: Color| color.to_srgba().to_f32_array_no_alpha();
+    let transform = TransformDescription::from(transform);
+
+    match light {
+        (Some(point), _, _) => LightDescription::Point {
+            color: srgb(point.color),
+            intensity: point.intensity,
+            range: point.range,
+            shadows: point.shadows_enabled,
+            transform,
+        },
+        (_, Some(directional), _) => LightDescription::Directional {
+            color: srgb(directional.color),
+            illuminance: directional.illuminance,
+            shadows: directional.shadows_enabled,
+            transform,
+        },
+        (_, _, Some(spot)) => LightDescription::Spot {
+            color: srgb(spot.color),
+            intensity: spot.intensity,
+            range: spot.range,
+            inner_angle: spot.inner_angle.to_degrees(),
+            outer_angle: spot.outer_angle.to_degrees(),
+            shadows: spot.shadows_enabled,
+            transform,
+        },
+        (None, None, None) => unreachable!("AnyOf matches at least one light"),
+    }
+}
+
+fn snapshot_scene(
+    mut commands: EventReader<SceneCommand>,
+    mut snapshots: EventWriter<SceneSnapshot>,
+    scene: SceneReader,
+) {
+    for command in commands.read() {
+        if *command == SceneCommand::Snapshot {
+            if let Some(description) = scene.read() {
+                snapshots.write(SceneSnapshot(description));
+            }
+        }
+    }
+}
+
+fn load_scene(
+    mut commands: Commands,
+    mut events: EventReader<SceneCommand>,
+    mut descriptions: ResMut<Assets<SceneDescription>>,
+    scenes: Query<(Entity, &DescribedScene)>,
+) {
+    for command in events.read() {
+        if let SceneCommand::Load(description) = command {
+            for (entity, scene) in &scenes {
+                descriptions.insert(&scene.0, description.clone());
+                commands.entity(entity).remove::<DescribedObjectIds>();
+            }
+        }
+    }
+}
+
+fn respawn_changed_scenes(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SceneDescription>>,
+    scenes: Query<(Entity, &DescribedScene), With<DescribedSceneSpawned>>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        for (entity, _) in scenes.iter().filter(|(_, scene)| scene.0.id() == *id) {
+            // The marker goes first, so removal hooks can tell a respawn from a deletion.
+            commands
+                .entity(entity)
+                .remove::<DescribedSceneSpawned>()
+                .despawn_related::<Children>();
+        }
+    }
+}
+
+fn spawn_described_scenes(
+    mut commands: Commands,
+    scenes: Query<
+        (Entity, &DescribedScene, Option<&DescribedObjectIds>),
+        Without<DescribedSceneSpawned>,
+    >,
+    descriptions: Res<Assets<SceneDescription>>,
+    mut assets: ObjectAssets,
+) {
+    for (entity, scene, ids) in &scenes {
+        let Some(description) = descriptions.get(&scene.0) else {
+            continue;
+        };
+
+        commands.entity(entity).insert((
+            DescribedSceneSpawned,
+            SceneEnvironment(description.environment.clone()),
+        ));
+        assets.library.clear();
+
+        for (index, object) in description.objects.iter().enumerate() {
+            let id = ids
+                .and_then(|DescribedObjectIds(ids)| ids.get(index).copied())
+                .unwrap_or(index as u64);
+            spawn_object(&mut commands, entity, id, object, &mut assets);
+        }
+
+        for light in &description.lights {
+            spawn_light(&mut commands, entity, light);
+        }
+
+        let camera = &description.camera;
+        let camera = OrbitCamera::new(camera.eye.into(), camera.focus.into());
+        commands.spawn((camera.transform(), camera, ChildOf(entity)));
+    }
+}
+
+/// The assets objects are made of.
+#[derive(SystemParam)]
+pub struct ObjectAssets<'w> {
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub library: ResMut<'w, MaterialLibrary>,
+    pub asset_server: Res<'w, AssetServer>,
+}
+
+impl ObjectAssets<'_> {
+    /// The described material.
+    ///
+    /// Named materials come from the `MaterialLibrary`. The first description with a name defines
+    /// that material, later ones only refer to it, so a scene file can name a material once and
+    /// reuse it by name.
+    pub fn material(&mut self, description: &MaterialDescription) -> Handle<StandardMaterial> {
+        if let Some(handle) = description
+            .name
+            .as_ref()
+            .and_then(|name| self.library.get(name))
+        {
+            return handle.clone();
+        }
+
+        let mut material = StandardMaterial::default();
+        description.apply(&mut material, &self.asset_server);
+        let handle = self.materials.add(material);
+        if let Some(name) = &description.name {
+            self.library.insert(name.clone(), handle.clone());
+        }
+        handle
+    }
+
+    /// Gives an object the described material, changing it for every object that shares it.
+    ///
+    /// An object that shares a named material gets a copy of its own when the description has no
+    /// name, so the other objects keep theirs.
+    pub fn set_material(
+        &mut self,
+        material: &mut MeshMaterial3d<StandardMaterial>,
+        description: &MaterialDescription,
+    ) {
+        let shared = self.library.name_of(&material.0).is_some();
+        if description.name.is_some() || shared {
+            material.0 = self.material(description);
+        }
+        if let Some(current) = self.materials.get_mut(&material.0) {
+            description.apply(current, &self.asset_server);
+        }
+    }
+}
+
+/// Spawns an object as a child of the `DescribedScene` entity `scene`.
+pub fn spawn_object(
+    commands: &mut Commands,
+    scene: Entity,
+    id: u64,
+    object: &ObjectDescription,
+    assets: &mut ObjectAssets,
+) -> Entity {
+    insert_object(commands.spawn_empty(), scene, id, object, assets)
+}
+
+/// Turns an empty entity into an object, as a child of the `DescribedScene` entity `scene`.
+pub fn insert_object(
+    mut entity: EntityCommands,
+    scene: Entity,
+    id: u64,
+    object: &ObjectDescription,
+    assets: &mut ObjectAssets,
+) -> Entity {
+    let mut mesh = Mesh::from(object.mesh);
+    // Normal maps need tangents, and a texture may be added to the material at any time.
+    if let Err(error) = mesh.generate_tangents() {
+        warn!("no tangents for {:?}: {error}", object.mesh);
+    }
+
+    entity.insert((
+        Mesh3d(assets.meshes.add(mesh)),
+        MeshMaterial3d(assets.material(&object.material)),
+        Transform::from(object.transform),
+        SceneObject {
+            id,
+            mesh: object.mesh,
+            roles: object.roles.clone(),
+            body: object.body,
+        },
+        Selectable,
+        ChildOf(scene),
+    ));
+
+    if let Some(name) = &object.name {
+        entity.insert(Name::new(name.clone()));
+    }
+    for role in &object.roles {
+        match role {
+            ObjectRole::Cube => entity.insert(Cube),
+            ObjectRole::TextAnchor => entity.insert(SceneTextAnchor),
+        };
+    }
+
+    entity.id()
+}
+
+/// Spawns a light as a child of the `DescribedScene` entity `scene`.
+pub fn spawn_light(commands: &mut Commands, scene: Entity, light: &LightDescription) -> Entity {
+    insert_light(commands.spawn_empty(), scene, light)
+}
+
+/// Turns an empty entity into a light, as a child of the `DescribedScene` entity `scene`.
+pub fn insert_light(mut entity: EntityCommands, scene: Entity, light: &LightDescription) -> Entity {
+    let srgb = |[red, green, blue]: [f32; 3]| Color::srgb(red, green, blue);
+
+    match *light {
+        LightDescription::Point {
+            color,
+            intensity,
+            range,
+            shadows,
+            transform,
+        } => entity.insert((
+            PointLight {
+                color: srgb(color),
+                intensity,
+                range,
+                shadows_enabled: shadows,
+                ..default()
+            },
+            Transform::from(transform),
+            ChildOf(scene),
+        )),
+        LightDescription::Directional {
+            color,
+            illuminance,
+            shadows,
+            transform,
+        } => entity.insert((
+            DirectionalLight {
+                color: srgb(color),
+                illuminance,
+                shadows_enabled: shadows,
+                ..default()
+            },
+            Transform::from(transform),
+            ChildOf(scene),
+        )),
+        LightDescription::Spot {
+            color,
+            intensity,
+            range,
+            inner_angle,
+            outer_angle,
+            shadows,
+            transform,
+        } => entity.insert((
+            SpotLight {
+                color: srgb(color),
+                intensity,
+                range,
+                inner_angle: inner_angle.to_radians(),
+                outer_angle: outer_angle.to_radians(),
+                shadows_enabled: shadows,
+                ..default()
+            },
+            Transform::from(transform),
+            ChildOf(scene),
+        )),
+    }
+    .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{SceneDescription, SceneObject};
+    use crate::orbit_camera::OrbitCamera;
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    #[test]
+    fn children_of_the_scene_are_placed_as_described() {
+        let scene: SceneDescription = ron::from_str(DEFAULT_SCENE).unwrap();
+        let mut app = headless_app(scene.clone());
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let world = app.world_mut();
+        let camera = world
+            .query_filtered::<&GlobalTransform, With<OrbitCamera>>()
+            .single(world)
+            .unwrap();
+        // The orbit camera works its transform out again from the eye and the focus.
+        assert!(camera
+            .translation()
+            .abs_diff_eq(Vec3::from(scene.camera.eye), 1e-5));
+
+        let cube = world
+            .query::<(&SceneObject, &GlobalTransform)>()
+            .iter(world)
+            .find(|(object, _)| object.id == 0)
+            .map(|(_, transform)| transform.translation())
+            .unwrap();
+        assert_eq!(cube, Vec3::from(scene.objects[0].transform.translation));
+    }
+}
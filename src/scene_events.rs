@@ -0,0 +1,74 @@
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+/// Events sent from the Bevy scene back to the Leptos page.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub enum SceneEvent {
+    /// An entity in the scene was clicked.
+    Clicked { entity: Entity },
+    /// The selection changed; `None` when it was cleared.
+    SelectionChanged { entity: Option<Entity> },
+    /// Smoothed frame rate, reported about once per second.
+    FrameStats { fps: f64 },
+}
+
+/// How often `SceneEvent::FrameStats` is written.
+#[derive(Resource, Deref, DerefMut)]
+struct FrameStatsTimer(Timer);
+
+impl Default for FrameStatsTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(1.0, TimerMode::Repeating))
+    }
+}
+
+/// Writes `SceneEvent`s for clicks on scene entities and for frame statistics.
+///
+/// Pair it with `export_event_to_leptos` to have the events show up in Leptos.
+pub struct SceneEventsPlugin;
+
+impl Plugin for SceneEventsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+
+        app.add_event::<SceneEvent>()
+            .init_resource::<FrameStatsTimer>()
+            .add_observer(report_click)
+            .add_systems(Update, report_frame_stats);
+    }
+}
+
+/// Reports clicks on meshes only, and only once per click even though the pointer event bubbles
+/// up through parents and the window.
+fn report_click(
+    trigger: Trigger<Pointer<Click>>,
+    meshes: Query<(), With<Mesh3d>>,
+    mut events: EventWriter<SceneEvent>,
+) {
+    let entity = trigger.target();
+
+    if entity == trigger.event().target && meshes.contains(entity) {
+        events.write(SceneEvent::Clicked { entity });
+    }
+}
+
+fn report_frame_stats(
+    time: Res<Time>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut timer: ResMut<FrameStatsTimer>,
+    mut events: EventWriter<SceneEvent>,
+) {
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed());
+
+    if let Some(fps) = fps {
+        events.write(SceneEvent::FrameStats { fps });
+    }
+}
@@ -0,0 +1,547 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::f32::consts::PI;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use gltf::json;
+use gltf::json::validation::Checked::Valid;
+use gltf::json::validation::USize64;
+
+use crate::scene_description::{DescribedScene, SceneDescription};
+
+/// Asks for the scene as binary glTF, sent from Leptos.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExportScene;
+
+/// The scene as a `.glb` file, in reply to `ExportScene`.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct ExportedScene(pub Vec<u8>);
+
+/// Replies to `ExportScene`s with the first `DescribedScene` as binary glTF.
+///
+/// Everything below the scene that has a `Transform` becomes a node: described objects, objects
+/// added later, imported models with their own children, lights and the camera. Meshes keep their
+/// positions, normals, first UVs and indices, and materials their `StandardMaterial` factors;
+/// textures are left out.
+pub struct SceneExportPlugin;
+
+impl Plugin for SceneExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportScene>()
+            .add_event::<ExportedScene>()
+            .add_systems(Update, export_scene);
+    }
+}
+
+fn export_scene(
+    mut commands: EventReader<ExportScene>,
+    mut exported: EventWriter<ExportedScene>,
+    exporter: SceneExporter,
+) {
+    // Several requests in one frame get the same file.
+    if commands.read().count() > 0 {
+        if let Some(glb) = exporter.to_glb() {
+            exported.write(ExportedScene(glb));
+        }
+    }
+}
+
+type NodeParts = (
+    &'static Transform,
+    Option<&'static Name>,
+    Option<&'static Children>,
+    Option<&'static Mesh3d>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Option<&'static Projection>,
+    (
+        Option<&'static PointLight>,
+        Option<&'static DirectionalLight>,
+        Option<&'static SpotLight>,
+    ),
+);
+
+/// Writes the spawned `DescribedScene` as binary glTF.
+#[derive(SystemParam)]
+pub struct SceneExporter<'w, 's> {
+    descriptions: Res<'w, Assets<SceneDescription>>,
+    meshes: Res<'w, Assets<Mesh>>,
+    materials: Res<'w, Assets<StandardMaterial>>,
+    scenes: Query<'w, 's, (&'static DescribedScene, &'static Children)>,
+    nodes: Query<'w, 's, NodeParts>,
+}
+
+impl SceneExporter<'_, '_> {
+    /// The first spawned scene as the contents of a `.glb` file.
+    pub fn to_glb(&self) -> Option<Vec<u8>> {
+        let (scene, children) = self.scenes.iter().next()?;
+        let title = self
+            .descriptions
+            .get(&scene.0)
+            .map(|description| description.title.clone())
+            .filter(|title| !title.is_empty());
+
+        let mut writer = GlbWriter::default();
+        let nodes = children
+            .iter()
+            .filter_map(|child| self.push_node(&mut writer, child))
+            .collect();
+        let scene = writer.root.push(json::Scene {
+            extensions: Default::default(),
+            extras: Default::default(),
+            name: title,
+            nodes,
+        });
+        writer.root.scene = Some(scene);
+
+        Some(writer.finish())
+    }
+
+    fn push_node(&self, writer: &mut GlbWriter, entity: Entity) -> Option<json::Index<json::Node>> {
+        let (transform, name, children, mesh, material, projection, lights) =
+            self.nodes.get(entity).ok()?;
+
+        let mesh = mesh
+            .and_then(|mesh| Some((mesh.id(), self.meshes.get(mesh)?)))
+            .and_then(|(id, mesh)| {
+                let material = material
+                    .and_then(|material| Some((material.id(), self.materials.get(material)?)));
+                writer.push_mesh(id, mesh, material)
+            });
+        let camera = match projection {
+            Some(Projection::Perspective(perspective)) => Some(writer.push_camera(perspective)),
+            _ => None,
+        };
+        let light = writer.push_light(lights);
+
+        let children: Vec<_> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| self.push_node(writer, *child))
+            .collect();
+
+        Some(writer.root.push(json::Node {
+            name: name.map(|name| name.to_string()),
+            translation: Some(transform.translation.to_array()),
+            rotation: Some(json::scene::UnitQuaternion(transform.rotation.to_array())),
+            scale: Some(transform.scale.to_array()),
+            mesh,
+            camera,
+            children: (!children.is_empty()).then_some(children),
+            extensions: light.map(|light| json::extensions::scene::Node {
+                khr_lights_punctual: Some(
+                    json::extensions::scene::khr_lights_punctual::KhrLightsPunctual { light },
+                ),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+    }
+}
+
+type MeshKey = (AssetId<Mesh>, Option<AssetId<StandardMaterial>>);
+
+/// Collects the glTF document and its binary buffer, sharing meshes and materials between nodes.
+#[derive(Default)]
+struct GlbWriter {
+    root: json::Root,
+    buffer: Vec<u8>,
+    meshes: HashMap<MeshKey, Option<json::Index<json::Mesh>>>,
+    materials: HashMap<AssetId<StandardMaterial>, json::Index<json::Material>>,
+    extensions: BTreeSet<&'static str>,
+}
+
+impl GlbWriter {
+    /// Only triangle meshes are written; `None` for any other.
+    fn push_mesh(
+        &mut self,
+        id: AssetId<Mesh>,
+        mesh: &Mesh,
+        material: Option<(AssetId<StandardMaterial>, &StandardMaterial)>,
+    ) -> Option<json::Index<json::Mesh>> {
+        let key = (id, material.map(|(id, _)| id));
+        if let Some(mesh) = self.meshes.get(&key) {
+            return *mesh;
+        }
+
+        let mesh = self.push_triangles(mesh).map(|mut primitive| {
+            primitive.material = material.map(|(id, material)| self.push_material(id, material));
+            self.root.push(json::Mesh {
+                extensions: Default::default(),
+                extras: Default::default(),
+                name: None,
+                primitives: vec![primitive],
+                weights: None,
+            })
+        });
+        self.meshes.insert(key, mesh);
+        mesh
+    }
+
+    fn push_triangles(&mut self, mesh: &Mesh) -> Option<json::mesh::Primitive> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+
+        let mut attributes = BTreeMap::new();
+        let positions = self.push_floats(positions, json::accessor::Type::Vec3, true);
+        attributes.insert(Valid(json::mesh::Semantic::Positions), positions);
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            let normals = self.push_floats(normals, json::accessor::Type::Vec3, false);
+            attributes.insert(Valid(json::mesh::Semantic::Normals), normals);
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            let uvs = self.push_floats(uvs, json::accessor::Type::Vec2, false);
+            attributes.insert(Valid(json::mesh::Semantic::TexCoords(0)), uvs);
+        }
+
+        let indices = mesh.indices().map(|indices| self.push_indices(indices));
+
+        Some(json::mesh::Primitive {
+            attributes,
+            extensions: Default::default(),
+            extras: Default::default(),
+            indices,
+            material: None,
+            mode: Valid(json::mesh::Mode::Triangles),
+            targets: None,
+        })
+    }
+
+    /// Appends the floats of `values` to the buffer, with their bounds if `bounds` is set, as
+    /// glTF requires for positions.
+    fn push_floats<const N: usize>(
+        &mut self,
+        values: &[[f32; N]],
+        type_: json::accessor::Type,
+        bounds: bool,
+    ) -> json::Index<json::Accessor> {
+        let bytes = values.iter().flatten().map(|value| value.to_le_bytes());
+        let view = self.push_view(bytes, json::buffer::Target::ArrayBuffer);
+
+        let (min, max) = if bounds {
+            let (min, max) = values.iter().fold(
+                ([f32::MAX; N], [f32::MIN; N]),
+                |(mut min, mut max), value| {
+                    for i in 0..N {
+                        min[i] = min[i].min(value[i]);
+                        max[i] = max[i].max(value[i]);
+                    }
+                    (min, max)
+                },
+            );
+            (Some(min.to_vec().into()), Some(max.to_vec().into()))
+        } else {
+            (None, None)
+        };
+
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: None,
+            count: USize64::from(values.len()),
+            component_type: Valid(json::accessor::GenericComponentType(
+                json::accessor::ComponentType::F32,
+            )),
+            extensions: Default::default(),
+            extras: Default::default(),
+            type_: Valid(type_),
+            min,
+            max,
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    fn push_indices(&mut self, indices: &Indices) -> json::Index<json::Accessor> {
+        let bytes = indices.iter().map(|index| (index as u32).to_le_bytes());
+        let view = self.push_view(bytes, json::buffer::Target::ElementArrayBuffer);
+
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: None,
+            count: USize64::from(indices.len()),
+            component_type: Valid(json::accessor::GenericComponentType(
+                json::accessor::ComponentType::U32,
+            )),
+            extensions: Default::default(),
+            extras: Default::default(),
+            type_: Valid(json::accessor::Type::Scalar),
+            min: None,
+            max: None,
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    /// Appends `bytes` to the buffer. All values are four bytes long, so views stay aligned.
+    fn push_view(
+        &mut self,
+        bytes: impl Iterator<Item = [u8; 4]>,
+        target: json::buffer::Target,
+    ) -> json::Index<json::buffer::View> {
+        let offset = self.buffer.len();
+        bytes.for_each(|bytes| self.buffer.extend_from_slice(&bytes));
+
+        self.root.push(json::buffer::View {
+            // The one buffer is pushed by `finish`.
+            buffer: json::Index::new(0),
+            byte_length: USize64::from(self.buffer.len() - offset),
+            byte_offset: Some(USize64::from(offset)),
+            byte_stride: None,
+            extensions: Default::default(),
+            extras: Default::default(),
+            name: None,
+            target: Some(Valid(target)),
+        })
+    }
+
+    fn push_material(
+        &mut self,
+        id: AssetId<StandardMaterial>,
+        material: &StandardMaterial,
+    ) -> json::Index<json::Material> {
+        if let Some(index) = self.materials.get(&id) {
+            return *index;
+        }
+
+        // glTF factors are linear, and emissive ones at most 1 unless they are strengthened.
+        let emissive = material.emissive;
+        let strength = emissive.red.max(emissive.green).max(emissive.blue).max(1.0);
+        let mut extensions = None::<json::extensions::material::Material>;
+        if strength > 1.0 {
+            self.extensions.insert("KHR_materials_emissive_strength");
+            extensions.get_or_insert_default().emissive_strength =
+                Some(json::extensions::material::EmissiveStrength {
+                    emissive_strength: json::extensions::material::EmissiveStrengthFactor(strength),
+                });
+        }
+        if material.unlit {
+            self.extensions.insert("KHR_materials_unlit");
+            extensions.get_or_insert_default().unlit = Some(json::extensions::material::Unlit {});
+        }
+
+        let (alpha_mode, alpha_cutoff) = match material.alpha_mode {
+            AlphaMode::Opaque => (json::material::AlphaMode::Opaque, None),
+            AlphaMode::Mask(cutoff) => (
+                json::material::AlphaMode::Mask,
+                Some(json::material::AlphaCutoff(cutoff)),
+            ),
+            _ => (json::material::AlphaMode::Blend, None),
+        };
+
+        let index = self.root.push(json::Material {
+            pbr_metallic_roughness: json::material::PbrMetallicRoughness {
+                base_color_factor: json::material::PbrBaseColorFactor(
+                    material.base_color.to_linear().to_f32_array(),
+                ),
+                metallic_factor: json::material::StrengthFactor(material.metallic),
+                roughness_factor: json::material::StrengthFactor(material.perceptual_roughness),
+                ..Default::default()
+            },
+            emissive_factor: json::material::EmissiveFactor(
+                (emissive.to_vec3() / strength).to_array(),
+            ),
+            alpha_mode: Valid(alpha_mode),
+            alpha_cutoff,
+            double_sided: material.double_sided,
+            extensions,
+            ..Default::default()
+        });
+        self.materials.insert(id, index);
+        index
+    }
+
+    fn push_camera(&mut self, perspective: &PerspectiveProjection) -> json::Index<json::Camera> {
+        self.root.push(json::Camera {
+            name: None,
+            orthographic: None,
+            perspective: Some(json::camera::Perspective {
+                aspect_ratio: Some(perspective.aspect_ratio),
+                yfov: perspective.fov,
+                zfar: Some(perspective.far),
+                znear: perspective.near,
+                extensions: Default::default(),
+                extras: Default::default(),
+            }),
+            type_: Valid(json::camera::Type::Perspective),
+            extensions: Default::default(),
+            extras: Default::default(),
+        })
+    }
+
+    /// Point and spot lights are given in candela in glTF, which Bevy gives as lumens.
+    fn push_light(
+        &mut self,
+        lights: (
+            Option<&PointLight>,
+            Option<&DirectionalLight>,
+            Option<&SpotLight>,
+        ),
+    ) -> Option<json::Index<json::extensions::scene::khr_lights_punctual::Light>> {
+        use json::extensions::scene::khr_lights_punctual::{Light, Spot, Type};
+
+        let linear = |color: Color| color.to_linear().to_vec3().to_array();
+        let (type_, color, intensity, range, spot) = match lights {
+            (Some(point), _, _) => (
+                Type::Point,
+                linear(point.color),
+                point.intensity / (4.0 * PI),
+                Some(point.range),
+                None,
+            ),
+            (_, Some(directional), _) => (
+                Type::Directional,
+                linear(directional.color),
+                directional.illuminance,
+                None,
+                None,
+            ),
+            (_, _, Some(spot)) => (
+                Type::Spot,
+                linear(spot.color),
+                spot.intensity / (4.0 * PI),
+                Some(spot.range),
+                Some(Spot {
+                    inner_cone_angle: spot.inner_angle,
+                    outer_cone_angle: spot.outer_angle,
+                }),
+            ),
+            (None, None, None) => return None,
+        };
+
+        self.extensions.insert("KHR_lights_punctual");
+        let lights = &mut self
+            .root
+            .extensions
+            .get_or_insert_with(Default::default)
+            .khr_lights_punctual
+            .get_or_insert_with(Default::default)
+            .lights;
+        lights.push(Light {
+            color,
+            extensions: None,
+            extras: Default::default(),
+            intensity,
+            name: None,
+            range,
+            spot,
+            type_: Valid(type_),
+        });
+        Some(json::Index::new(lights.len() as u32 - 1))
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if !self.buffer.is_empty() {
+            self.root.push(json::Buffer {
+                byte_length: USize64::from(self.buffer.len()),
+                extensions: Default::default(),
+                extras: Default::default(),
+                name: None,
+                uri: None,
+            });
+        }
+        self.root.extensions_used = self
+            .extensions
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let json = json::serialize::to_vec(&self.root).expect("glTF documents always serialize");
+        let glb = gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                // Filled in while writing.
+                length: 0,
+            },
+            json: Cow::Owned(json),
+            bin: (!self.buffer.is_empty()).then_some(Cow::Owned(self.buffer)),
+        };
+        glb.to_vec().expect("writing to a Vec doesn't fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+
+    use super::SceneExporter;
+    use crate::scene_description::{
+        MaterialDescription, MeshDescription, ObjectDescription, SceneDescription,
+        TransformDescription,
+    };
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    #[test]
+    fn exported_objects_keep_their_transforms_and_colors() {
+        let mut scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        scene.objects.push(ObjectDescription {
+            name: Some("Ball".to_string()),
+            mesh: MeshDescription::Sphere { radius: 0.5 },
+            material: MaterialDescription {
+                base_color: [0.8, 0.2, 0.1, 1.0],
+                ..default()
+            },
+            transform: TransformDescription {
+                translation: [-2.0, 1.0, 0.5],
+                rotation: [0.0, 45.0, 30.0],
+                scale: [2.0, 1.5, 2.0],
+            },
+            roles: Vec::new(),
+            body: None,
+        });
+        let mut app = headless_app(scene.clone());
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let glb = app
+            .world_mut()
+            .run_system_once(|exporter: SceneExporter| exporter.to_glb())
+            .unwrap()
+            .unwrap();
+        let gltf = gltf::Gltf::from_slice(&glb).unwrap();
+        let nodes: Vec<_> = gltf.default_scene().unwrap().nodes().collect();
+
+        for object in &scene.objects {
+            let name = object.name.as_deref();
+            let node = nodes.iter().find(|node| node.name() == name).unwrap();
+
+            let (translation, rotation, scale) = node.transform().decomposed();
+            let expected = Transform::from(object.transform);
+            assert!(Vec3::from(translation).abs_diff_eq(expected.translation, 1e-5));
+            assert!(Quat::from_array(rotation).abs_diff_eq(expected.rotation, 1e-5));
+            assert!(Vec3::from(scale).abs_diff_eq(expected.scale, 1e-5));
+
+            let [red, green, blue, alpha] = object.material.base_color;
+            let expected = LinearRgba::from(Color::srgba(red, green, blue, alpha));
+            for primitive in node.mesh().unwrap().primitives() {
+                let color = primitive
+                    .material()
+                    .pbr_metallic_roughness()
+                    .base_color_factor();
+                assert!(
+                    Vec4::from(color).abs_diff_eq(expected.to_vec4(), 1e-5),
+                    "{name:?}"
+                );
+            }
+        }
+
+        let light = nodes.iter().find(|node| node.light().is_some()).unwrap();
+        let (translation, _, _) = light.transform().decomposed();
+        assert_eq!(translation, [4.0, 8.0, 4.0]);
+    }
+}
@@ -0,0 +1,255 @@
+use leptos::prelude::*;
+use leptos::server_fn::codec::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::scene_description::SceneDescription;
+
+/// A stored scene as it is listed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SceneInfo {
+    pub id: String,
+    pub title: String,
+}
+
+/// Stores the scene under a new id and returns it.
+#[server(input = Json)]
+pub async fn save_scene(scene: SceneDescription) -> Result<SceneInfo, ServerFnError> {
+    let store = expect_context::<store::SceneStore>();
+    Ok(store.save(&scene).await?)
+}
+
+/// All stored scenes, oldest first.
+#[server]
+pub async fn list_scenes() -> Result<Vec<SceneInfo>, ServerFnError> {
+    let store = expect_context::<store::SceneStore>();
+    Ok(store.list().await?)
+}
+
+/// The stored scene with this id, or `None` if there is none.
+#[server]
+pub async fn load_scene(id: String) -> Result<Option<SceneDescription>, ServerFnError> {
+    let store = expect_context::<store::SceneStore>();
+    Ok(store.load(&id).await?)
+}
+
+/// Deletes the stored scene with this id, if there is one.
+#[server]
+pub async fn delete_scene(id: String) -> Result<(), ServerFnError> {
+    let store = expect_context::<store::SceneStore>();
+    Ok(store.delete(&id).await?)
+}
+
+/// -------- Storage --------
+#[cfg(feature = "ssr")]
+pub mod store {
+    use std::io::{Error, ErrorKind};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use tokio::fs;
+
+    use super::SceneInfo;
+    use crate::scene_description::SceneDescription;
+
+    const EXTENSION: &str = ".scene.ron";
+
+    /// Keeps scenes as `<id>.scene.ron` files in a directory.
+    ///
+    /// Ids are hex timestamps, so they sort in the order the scenes were saved.
+    #[derive(Clone, Debug)]
+    pub struct SceneStore {
+        dir: PathBuf,
+    }
+
+    impl SceneStore {
+        pub fn new(dir: impl Into<PathBuf>) -> Self {
+            Self { dir: dir.into() }
+        }
+
+        /// Uses the directory in `SCENE_DIR`, or `data/scenes`.
+        pub fn from_env() -> Self {
+            Self::new(std::env::var("SCENE_DIR").unwrap_or_else(|_| "data/scenes".to_string()))
+        }
+
+        pub async fn save(&self, scene: &SceneDescription) -> Result<SceneInfo, Error> {
+            fs::create_dir_all(&self.dir).await?;
+
+            let mut millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(Error::other)?
+                .as_millis();
+            // The scene is written next to the others first and then linked into place, so a
+            // stored scene is complete as soon as it appears. Linking fails for ids that are
+            // taken, so scenes saved at the same time get different ones.
+            static PARTS: AtomicU64 = AtomicU64::new(0);
+            let part = self.dir.join(format!(
+                "{}-{}.part",
+                std::process::id(),
+                PARTS.fetch_add(1, Ordering::Relaxed)
+            ));
+            let claimed = match fs::write(&part, scene.to_ron()).await {
+                Ok(()) => loop {
+                    let id = format!("{millis:012x}");
+                    match fs::hard_link(&part, self.dir.join(format!("{id}{EXTENSION}"))).await {
+                        Ok(()) => break Ok(id),
+                        Err(err) if err.kind() == ErrorKind::AlreadyExists => millis += 1,
+                        Err(err) => break Err(err),
+                    }
+                },
+                Err(err) => Err(err),
+            };
+            fs::remove_file(&part).await.ok();
+            let id = claimed?;
+
+            Ok(SceneInfo {
+                id,
+                title: scene.title.clone(),
+            })
+        }
+
+        pub async fn list(&self) -> Result<Vec<SceneInfo>, Error> {
+            let mut entries = match fs::read_dir(&self.dir).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(err) => return Err(err),
+            };
+
+            let mut scenes = Vec::new();
+            while let Some(entry) = entries.next_entry().await? {
+                let file_name = entry.file_name();
+                let Some(id) = file_name
+                    .to_str()
+                    .and_then(|name| name.strip_suffix(EXTENSION))
+                else {
+                    continue;
+                };
+
+                // Files that don't parse are not scenes this store can hand out.
+                if let Ok(Some(scene)) = self.load(id).await {
+                    scenes.push(SceneInfo {
+                        id: id.to_string(),
+                        title: scene.title,
+                    });
+                }
+            }
+
+            scenes.sort_by(|a, b| a.id.cmp(&b.id));
+            Ok(scenes)
+        }
+
+        pub async fn load(&self, id: &str) -> Result<Option<SceneDescription>, Error> {
+            // No scene is ever stored under an id that `path` rejects.
+            let Ok(path) = self.path(id) else {
+                return Ok(None);
+            };
+
+            let text = match fs::read_to_string(path).await {
+                Ok(text) => text,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            };
+
+            SceneDescription::from_ron(&text)
+                .map(Some)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+        }
+
+        pub async fn delete(&self, id: &str) -> Result<(), Error> {
+            match fs::remove_file(self.path(id)?).await {
+                Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        }
+
+        /// Ids come from the client, so anything that could leave the directory is rejected.
+        fn path(&self, id: &str) -> Result<PathBuf, Error> {
+            if is_valid_id(id) {
+                Ok(self.dir.join(format!("{id}{EXTENSION}")))
+            } else {
+                Err(Error::new(ErrorKind::InvalidInput, "invalid scene id"))
+            }
+        }
+    }
+
+    /// Whether a scene could be stored under `id`, which is then safe to use in file names.
+    pub fn is_valid_id(id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::BTreeSet;
+
+        use super::SceneStore;
+        use crate::scene_description::SceneDescription;
+
+        const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn scenes_saved_at_once_get_their_own_ids() {
+            let dir = std::env::temp_dir().join(format!("bevytos-scenes-{}", std::process::id()));
+            let store = SceneStore::new(&dir);
+
+            let saves: Vec<_> = (0..16)
+                .map(|index| {
+                    let store = store.clone();
+                    tokio::spawn(async move {
+                        let mut scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+                        scene.title = format!("Scene {index}");
+                        store.save(&scene).await.unwrap()
+                    })
+                })
+                .collect();
+            // Every scene file that shows up while the others are saved is already complete.
+            let mut unfinished = Vec::new();
+            while !saves.iter().all(|save| save.is_finished()) {
+                let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+                    continue;
+                };
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if name.ends_with(".scene.ron") {
+                        let text = tokio::fs::read_to_string(entry.path()).await.unwrap();
+                        if SceneDescription::from_ron(&text).is_err() {
+                            unfinished.push(name);
+                        }
+                    }
+                }
+            }
+            let mut saved = Vec::new();
+            for save in saves {
+                saved.push(save.await.unwrap());
+            }
+
+            let listed = store.list().await.unwrap();
+            let mut files = tokio::fs::read_dir(&dir).await.unwrap();
+            let mut file_count = 0;
+            while files.next_entry().await.unwrap().is_some() {
+                file_count += 1;
+            }
+            tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+            assert_eq!(unfinished, Vec::<String>::new());
+            assert_eq!(file_count, saved.len());
+
+            let ids: BTreeSet<_> = saved.iter().map(|info| info.id.clone()).collect();
+            assert_eq!(ids.len(), saved.len());
+            assert_eq!(listed.len(), saved.len());
+            for info in saved {
+                assert!(listed.contains(&info));
+            }
+        }
+
+        #[tokio::test]
+        async fn invalid_ids_name_no_scene() {
+            let store = SceneStore::new(std::env::temp_dir().join("bevytos-invalid-ids"));
+            for id in ["", "a.b", "../default", "a/b"] {
+                assert_eq!(store.load(id).await.unwrap(), None, "{id:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use crate::viewport::ViewportCamera;
+
+/// Font, size and color of the text shown in the scene.
+#[derive(Resource, Clone, Debug)]
+pub struct SceneTextStyle {
+    /// Defaults to Bevy's built-in font.
+    pub font: Handle<Font>,
+    pub font_size: f32,
+    pub color: Color,
+    /// World-space offset of the label from the entity it floats above.
+    pub offset: Vec3,
+}
+
+impl Default for SceneTextStyle {
+    fn default() -> Self {
+        Self {
+            font: default(),
+            font_size: 28.0,
+            color: Color::WHITE,
+            offset: Vec3::new(0.0, 0.9, 0.0),
+        }
+    }
+}
+
+/// Marks the entity the scene text floats above.
+#[derive(Component)]
+pub struct SceneTextAnchor;
+
+/// The label that shows the latest text sent from Leptos.
+#[derive(Component)]
+pub struct SceneText;
+
+/// Renders a billboarded label above the `SceneTextAnchor` entity.
+///
+/// The label always faces the camera because it is laid out in screen space at the projected
+/// position of the anchor.
+pub struct SceneTextPlugin;
+
+impl Plugin for SceneTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneTextStyle>()
+            .add_systems(Startup, spawn_scene_text)
+            .add_systems(
+                PostUpdate,
+                (apply_scene_text_style, follow_anchor)
+                    .chain()
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn spawn_scene_text(mut commands: Commands, style: Res<SceneTextStyle>) {
+    commands.spawn((
+        SceneText,
+        Text::default(),
+        TextFont {
+            font: style.font.clone(),
+            font_size: style.font_size,
+            ..default()
+        },
+        TextColor(style.color),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+fn apply_scene_text_style(
+    style: Res<SceneTextStyle>,
+    mut label: Query<(&mut TextFont, &mut TextColor), With<SceneText>>,
+) {
+    if !style.is_changed() {
+        return;
+    }
+
+    for (mut font, mut color) in &mut label {
+        font.font = style.font.clone();
+        font.font_size = style.font_size;
+        color.0 = style.color;
+    }
+}
+
+fn follow_anchor(
+    style: Res<SceneTextStyle>,
+    camera: Single<(&Camera, &GlobalTransform), Without<ViewportCamera>>,
+    anchor: Single<&GlobalTransform, With<SceneTextAnchor>>,
+    mut label: Single<(&Text, &mut Node, &ComputedNode, &mut Visibility), With<SceneText>>,
+) {
+    let (camera, camera_transform) = *camera;
+    let (text, ref mut node, computed, ref mut visibility) = *label;
+
+    let position = camera.world_to_viewport(camera_transform, anchor.translation() + style.offset);
+
+    match position {
+        Ok(position) if !text.is_empty() => {
+            let size = computed.size() * computed.inverse_scale_factor();
+            node.left = Val::Px(position.x - size.x / 2.0);
+            node.top = Val::Px(position.y - size.y);
+            **visibility = Visibility::Inherited;
+        }
+        _ => **visibility = Visibility::Hidden,
+    }
+}
@@ -0,0 +1,700 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use bevy::app::PluginGroupBuilder;
+use bevy::picking::mesh_picking::MeshPickingPlugin;
+use bevy::prelude::*;
+use leptos::prelude::*;
+use leptos_bevy_canvas::prelude::*;
+
+use crate::cube_color::CubeColorPlugin;
+use crate::history::HistoryPlugin;
+#[cfg(target_arch = "wasm32")]
+use crate::keyboard_focus::KeyboardFocus;
+use crate::keyboard_focus::KeyboardFocusPlugin;
+use crate::lighting::LightingPlugin;
+use crate::orbit_camera::OrbitCameraPlugin;
+use crate::scene_description::SceneDescriptionPlugin;
+use crate::scene_events::SceneEventsPlugin;
+use crate::scene_text::SceneTextPlugin;
+use crate::selection::SelectionPlugin;
+#[cfg(target_arch = "wasm32")]
+use crate::viewport::OffscreenCanvas;
+use crate::viewport::{ViewportCommand, ViewportFrame, ViewportFrames, ViewportPlugin};
+#[cfg(target_arch = "wasm32")]
+use bevy::render::renderer::{
+    RenderAdapter, RenderAdapterInfo, RenderDevice, RenderInstance, RenderQueue,
+};
+#[cfg(target_arch = "wasm32")]
+use bevy::render::settings::RenderResources;
+#[cfg(target_arch = "wasm32")]
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
+use std::rc::Rc;
+
+/// How the Bevy canvas is sized on the page.
+///
+/// The canvas always fills its container and renders at the device pixel ratio.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CanvasFit {
+    /// Full width of the page, with the height following this width / height ratio.
+    AspectRatio(f32),
+    /// Full width and height of the parent element, which needs a definite height.
+    Fill,
+    /// Fixed size in CSS pixels.
+    Fixed { width: f32, height: f32 },
+}
+
+impl CanvasFit {
+    /// Inline style for the element wrapping the canvas.
+    pub fn style(self) -> String {
+        match self {
+            CanvasFit::AspectRatio(ratio) => format!("width: 100%; aspect-ratio: {ratio};"),
+            CanvasFit::Fill => "width: 100%; height: 100%;".to_string(),
+            CanvasFit::Fixed { width, height } => format!("width: {width}px; height: {height}px;"),
+        }
+    }
+}
+
+/// Setup applied to the Bevy app of a `SceneView` before it starts running, like spawning the
+/// scene, adding plugins or importing Leptos events.
+pub struct ExtraPlugins(Box<dyn FnOnce(&mut App)>);
+
+impl<F> From<F> for ExtraPlugins
+where
+    F: FnOnce(&mut App) + 'static,
+{
+    fn from(setup: F) -> Self {
+        Self(Box::new(setup))
+    }
+}
+
+impl ExtraPlugins {
+    pub fn apply(self, app: &mut App) {
+        (self.0)(app);
+    }
+}
+
+/// The plugins every `SceneView` app gets on top of `DefaultPlugins`.
+pub struct ScenePlugins;
+
+impl PluginGroup for ScenePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(SceneDescriptionPlugin)
+            .add(LightingPlugin)
+            .add(SceneTextPlugin)
+            .add(SceneEventsPlugin)
+            .add(CubeColorPlugin)
+            .add(HistoryPlugin)
+            // `DefaultPlugins` pick sprites and UI only, while clicks have to find the objects.
+            .add(MeshPickingPlugin)
+            .add(SelectionPlugin)
+            .add(OrbitCameraPlugin)
+            .add(ViewportPlugin)
+            .add(KeyboardFocusPlugin)
+    }
+}
+
+static NEXT_CANVAS_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Generates a canvas id that is unique within the page.
+///
+/// While rendering, the ids come from the shared context of the page, which numbers them in the
+/// order the views render, so the server and hydration give every canvas the same one. Outside of
+/// a render they are only unique within the process.
+pub fn next_canvas_id() -> String {
+    match Owner::current_shared_context() {
+        Some(context) => format!("bevy_canvas_{}", context.next_id().into_inner()),
+        None => format!(
+            "bevy_canvas_local_{}",
+            NEXT_CANVAS_ID.fetch_add(1, Ordering::Relaxed)
+        ),
+    }
+}
+
+/// Set by `SceneView` when it unmounts to stop its Bevy app.
+#[derive(Resource, Clone, Default)]
+pub struct SceneLifetime(Arc<AtomicBool>);
+
+impl SceneLifetime {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Exits the app once its `SceneLifetime` is stopped, which closes all of its windows and drops
+/// the app together with its GPU resources and Leptos bridges.
+pub struct SceneLifetimePlugin(pub SceneLifetime);
+
+impl Plugin for SceneLifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .add_systems(Last, exit_when_stopped);
+    }
+}
+
+fn exit_when_stopped(lifetime: Res<SceneLifetime>, mut exit: EventWriter<AppExit>) {
+    if lifetime.is_stopped() {
+        exit.write(AppExit::Success);
+    }
+}
+
+/// Lets `SceneViewport`s open extra canvases in the app of the surrounding `SceneView`.
+#[derive(Clone)]
+pub struct SceneViewports {
+    commands: LeptosEventSender<ViewportCommand>,
+    frames: LeptosEventReceiver<ViewportFrames>,
+}
+
+/// The Bevy ends of the `SceneViewports` bridges.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+struct BevyViewports {
+    commands: BevyEventReceiver<ViewportCommand>,
+    frames: BevyEventSender<ViewportFrames>,
+}
+
+/// Embeds a Bevy scene in the page.
+///
+/// Every view runs its own Bevy app, with the scene itself and any Leptos bridges added through
+/// `plugins`, so a page can show several independent scenes. To show the same scene from another
+/// camera, add `SceneViewport` children instead.
+///
+/// The browser only runs one winit event loop per page, so only the first view mounted renders
+/// straight into its canvas and gets pointer input. The views mounted while it runs render
+/// offscreen with its GPU device and have their frames copied into their canvas, like
+/// `SceneViewport`s, at the size the canvas had when their app started.
+///
+/// The canvas can be focused by clicking or tabbing to it, and only receives keyboard input while
+/// it is.
+#[component]
+pub fn SceneView(
+    /// Id of the canvas element. A unique one is generated when not given.
+    #[prop(optional, into)]
+    canvas_id: Option<String>,
+    /// Initial sizing of the canvas. Defaults to a 16:9 aspect ratio.
+    #[prop(default = CanvasFit::AspectRatio(16.0 / 9.0))]
+    fit: CanvasFit,
+    /// Color the scene is cleared to.
+    #[prop(default = ClearColor::default().0)]
+    background: Color,
+    /// Extra setup for the Bevy app.
+    #[prop(optional, into)]
+    plugins: Option<ExtraPlugins>,
+    /// `SceneViewport`s and anything else rendered next to the canvas.
+    #[prop(optional)]
+    children: Option<leptos::prelude::Children>,
+) -> impl IntoView {
+    let canvas_id = canvas_id.unwrap_or_else(next_canvas_id);
+    let (command_sender, bevy_command_receiver) = event_l2b::<ViewportCommand>();
+    let (frame_receiver, bevy_frame_sender) = event_b2l::<ViewportFrames>();
+
+    provide_context(SceneViewports {
+        commands: command_sender,
+        frames: frame_receiver.clone(),
+    });
+
+    let viewports = BevyViewports {
+        commands: bevy_command_receiver,
+        frames: bevy_frame_sender,
+    };
+
+    view! {
+        <div class="canvas-container" style=fit.style()>
+            {scene_canvas(canvas_id, background, plugins, viewports, frame_receiver)}
+        </div>
+        {children.map(|children| children())}
+    }
+}
+
+/// An extra canvas showing the scene of the surrounding `SceneView` from another camera.
+///
+/// The scene is rendered offscreen at `size` pixels and copied into the canvas, which makes it
+/// best suited for small previews.
+#[component]
+pub fn SceneViewport(
+    /// Id of the canvas element. A unique one is generated when not given.
+    #[prop(optional, into)]
+    canvas_id: Option<String>,
+    /// Initial sizing of the canvas. Defaults to a 16:9 aspect ratio.
+    #[prop(default = CanvasFit::AspectRatio(16.0 / 9.0))]
+    fit: CanvasFit,
+    /// Rendered resolution in pixels.
+    #[prop(default = UVec2::new(256, 144))]
+    size: UVec2,
+    /// Where the viewport camera is placed.
+    #[prop(default = Transform::from_xyz(3.0, 3.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y))]
+    camera: Transform,
+) -> impl IntoView {
+    let canvas_id = canvas_id.unwrap_or_else(next_canvas_id);
+    let SceneViewports { commands, frames } = expect_context::<SceneViewports>();
+    let canvas_ref = NodeRef::<leptos::html::Canvas>::new();
+
+    commands
+        .send(ViewportCommand::Open {
+            canvas_id: canvas_id.clone(),
+            camera,
+            size,
+        })
+        .ok();
+
+    on_cleanup({
+        let canvas_id = canvas_id.clone();
+        let commands = commands.clone();
+        move || {
+            commands.send(ViewportCommand::Close { canvas_id }).ok();
+        }
+    });
+
+    draw_frames(canvas_id.clone(), frames, canvas_ref);
+
+    view! {
+        <div class="canvas-container" style=fit.style()>
+            <canvas id=canvas_id node_ref=canvas_ref width=size.x height=size.y></canvas>
+        </div>
+    }
+}
+
+//
+// CLIENT-SIDE (wasm32) IMPLEMENTATION
+//
+/// Set while a mounted `SceneView` runs its app in the winit event loop, which the browser only
+/// allows once per page. Views mounted meanwhile render offscreen with its renderer.
+#[cfg(target_arch = "wasm32")]
+static WINIT_CLAIMED: AtomicBool = AtomicBool::new(false);
+
+/// Set while an app running in the winit event loop exists, from its start until it is dropped
+/// after exiting.
+#[cfg(target_arch = "wasm32")]
+static WINIT_APP_ALIVE: AtomicBool = AtomicBool::new(false);
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// The renderer of the app running in the winit event loop, which the apps of the other views
+    /// render with.
+    static SHARED_RENDERER: RefCell<Option<SharedRenderer>> = const { RefCell::new(None) };
+}
+
+/// The WebGL context of the winit app's canvas, and the device it was set up with once the
+/// renderer is ready.
+///
+/// A WebGL device can only be created for a canvas, and only presents to that one, so the other
+/// apps can't have a device of their own.
+#[cfg(target_arch = "wasm32")]
+struct SharedRenderer {
+    context: Rc<WebGlContext>,
+    resources: Option<RenderResources>,
+}
+
+#[cfg(target_arch = "wasm32")]
+fn scene_canvas(
+    canvas_id: String,
+    background: Color,
+    plugins: Option<ExtraPlugins>,
+    viewports: BevyViewports,
+    frames: LeptosEventReceiver<ViewportFrames>,
+) -> impl IntoView {
+    let runs_winit = !WINIT_CLAIMED.swap(true, Ordering::Relaxed);
+
+    let (keyboard_focus, bevy_keyboard_focus) = signal_synced(KeyboardFocus::default());
+    track_keyboard_focus(canvas_id.clone(), keyboard_focus);
+
+    let canvas_ref = NodeRef::<leptos::html::Canvas>::new();
+    if !runs_winit {
+        draw_frames(canvas_id.clone(), frames, canvas_ref);
+    }
+
+    let lifetime = SceneLifetime::default();
+    on_cleanup({
+        let lifetime = lifetime.clone();
+        move || {
+            lifetime.stop();
+            if runs_winit {
+                WINIT_CLAIMED.store(false, Ordering::Relaxed);
+            }
+        }
+    });
+
+    start_when_idle(
+        lifetime.clone(),
+        runs_winit,
+        Box::new({
+            let canvas_id = canvas_id.clone();
+            move || {
+                init_scene_app(
+                    canvas_id,
+                    background,
+                    plugins,
+                    viewports,
+                    bevy_keyboard_focus,
+                    lifetime,
+                    runs_winit,
+                )
+            }
+        }),
+    );
+
+    view! { <canvas id=canvas_id node_ref=canvas_ref tabindex="0"></canvas> }
+}
+
+/// Keeps `focus` up to date with which element of the page has keyboard focus.
+#[cfg(target_arch = "wasm32")]
+fn track_keyboard_focus(canvas_id: String, focus: RwSignalSynced<KeyboardFocus>) {
+    use web_sys::wasm_bindgen::JsCast;
+    use web_sys::HtmlElement;
+
+    // Only changes are sent, so the app isn't flooded while it starts.
+    let set_focus = move |value| {
+        if focus.get_untracked() != value {
+            focus.set(value);
+        }
+    };
+
+    let focus_in = window_event_listener(leptos::ev::focusin, move |event| {
+        let target = event
+            .target()
+            .and_then(|target| target.dyn_into::<HtmlElement>().ok());
+
+        set_focus(match target {
+            Some(element) if element.id() == canvas_id => KeyboardFocus::Canvas,
+            Some(element)
+                if element.is_content_editable()
+                    || matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT") =>
+            {
+                KeyboardFocus::FormElement
+            }
+            _ => KeyboardFocus::Page,
+        });
+    });
+    let focus_out = window_event_listener(leptos::ev::focusout, move |event| {
+        if event.related_target().is_none() {
+            set_focus(KeyboardFocus::Page);
+        }
+    });
+
+    on_cleanup(move || {
+        focus_in.remove();
+        focus_out.remove();
+    });
+}
+
+/// Runs the app once the canvas is mounted and, if it runs winit, the winit app of a previous
+/// `SceneView` is gone, or else once the winit app has a renderer to share.
+///
+/// An app exits on the frame after its view unmounts, and the browser only allows one winit event
+/// loop at a time, so coming back to a page quickly has to wait for the old one to be dropped.
+#[cfg(target_arch = "wasm32")]
+fn start_when_idle(lifetime: SceneLifetime, runs_winit: bool, init: Box<dyn FnOnce() -> App>) {
+    request_animation_frame(move || {
+        if lifetime.is_stopped() {
+            return;
+        }
+
+        let ready = if runs_winit {
+            !WINIT_APP_ALIVE.load(Ordering::Relaxed)
+        } else {
+            SHARED_RENDERER.with_borrow(|shared| {
+                shared
+                    .as_ref()
+                    .is_some_and(|shared| shared.resources.is_some())
+            })
+        };
+
+        if ready {
+            init().run();
+        } else {
+            start_when_idle(lifetime, runs_winit, init);
+        }
+    });
+}
+
+/// Releases a WebGL context once no app renders with it anymore.
+///
+/// Browsers only keep a limited number of contexts alive and would otherwise hold on to the one
+/// of an unmounted canvas until it is garbage collected.
+#[cfg(target_arch = "wasm32")]
+struct WebGlContext(Option<web_sys::HtmlCanvasElement>);
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for WebGlContext {
+    fn drop(&mut self) {
+        use web_sys::wasm_bindgen::JsCast;
+        use web_sys::{WebGl2RenderingContext, WebglLoseContext};
+
+        let lose_context = self
+            .0
+            .as_ref()
+            .and_then(|canvas| canvas.get_context("webgl2").ok().flatten())
+            .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
+            .and_then(|context| context.get_extension("WEBGL_lose_context").ok().flatten())
+            .and_then(|extension| extension.dyn_into::<WebglLoseContext>().ok());
+
+        if let Some(lose_context) = lose_context {
+            lose_context.lose_context();
+        }
+    }
+}
+
+/// Keeps the WebGL context the app renders with alive until the app is dropped, and for the winit
+/// app, shares it with the apps of other views until then.
+#[cfg(target_arch = "wasm32")]
+struct SceneCanvas {
+    _context: Rc<WebGlContext>,
+    runs_winit: bool,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SceneCanvas {
+    fn new(canvas: Option<web_sys::HtmlCanvasElement>, runs_winit: bool) -> Self {
+        let context = if runs_winit {
+            WINIT_APP_ALIVE.store(true, Ordering::Relaxed);
+
+            let context = Rc::new(WebGlContext(canvas));
+            SHARED_RENDERER.set(Some(SharedRenderer {
+                context: context.clone(),
+                resources: None,
+            }));
+            context
+        } else {
+            SHARED_RENDERER.with_borrow(|shared| {
+                shared
+                    .as_ref()
+                    .expect("apps without winit start once the winit app shares its renderer")
+                    .context
+                    .clone()
+            })
+        };
+
+        Self {
+            _context: context,
+            runs_winit,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for SceneCanvas {
+    fn drop(&mut self) {
+        if self.runs_winit {
+            SHARED_RENDERER.take();
+            WINIT_APP_ALIVE.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Shares the renderer of the winit app once it is set up.
+#[cfg(target_arch = "wasm32")]
+fn share_renderer(
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    adapter_info: Res<RenderAdapterInfo>,
+    adapter: Res<RenderAdapter>,
+    instance: Res<RenderInstance>,
+) {
+    SHARED_RENDERER.with_borrow_mut(|shared| {
+        if let Some(shared) = shared.as_mut().filter(|shared| shared.resources.is_none()) {
+            shared.resources = Some(RenderResources(
+                device.clone(),
+                queue.clone(),
+                adapter_info.clone(),
+                adapter.clone(),
+                instance.clone(),
+            ));
+        }
+    });
+}
+
+/// Updates an app without winit in every animation frame, and drops it once it exits.
+///
+/// `ScheduleRunnerPlugin` would keep the app alive after it exits in the browser.
+#[cfg(target_arch = "wasm32")]
+fn run_in_animation_frames(app: App) -> AppExit {
+    next_animation_frame(app);
+    AppExit::Success
+}
+
+#[cfg(target_arch = "wasm32")]
+fn next_animation_frame(mut app: App) {
+    use bevy::app::PluginsState;
+
+    request_animation_frame(move || {
+        match app.plugins_state() {
+            PluginsState::Adding => {}
+            PluginsState::Ready => {
+                app.finish();
+                app.cleanup();
+            }
+            PluginsState::Finished => app.cleanup(),
+            PluginsState::Cleaned => {
+                app.update();
+                if app.should_exit().is_some() {
+                    return;
+                }
+            }
+        }
+
+        next_animation_frame(app);
+    });
+}
+
+/// Draws the frames rendered for the canvas with this id into it.
+fn draw_frames(
+    canvas_id: String,
+    frames: LeptosEventReceiver<ViewportFrames>,
+    canvas_ref: NodeRef<leptos::html::Canvas>,
+) {
+    Effect::new(move || {
+        // `With` is shadowed by Bevy's query filter of the same name.
+        leptos::prelude::With::with(&frames, |frames| {
+            let frame = frames
+                .iter()
+                .flat_map(|frames| &frames.0)
+                .find(|frame| frame.canvas_id == canvas_id);
+
+            if let (Some(frame), Some(canvas)) = (frame, canvas_ref.get()) {
+                draw_frame(&canvas, frame);
+            }
+        })
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn draw_frame(canvas: &web_sys::HtmlCanvasElement, frame: &ViewportFrame) {
+    use web_sys::wasm_bindgen::{Clamped, JsCast};
+    use web_sys::{CanvasRenderingContext2d, ImageData};
+
+    if (canvas.width(), canvas.height()) != (frame.size.x, frame.size.y) {
+        canvas.set_width(frame.size.x);
+        canvas.set_height(frame.size.y);
+    }
+
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok());
+    let image = ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(&frame.pixels),
+        frame.size.x,
+        frame.size.y,
+    );
+
+    if let (Some(context), Ok(image)) = (context, image) {
+        context.put_image_data(&image, 0.0, 0.0).ok();
+    }
+}
+
+//
+// SERVER-SIDE PLACEHOLDER
+//
+#[cfg(not(target_arch = "wasm32"))]
+fn scene_canvas(
+    canvas_id: String,
+    _background: Color,
+    _plugins: Option<ExtraPlugins>,
+    _viewports: BevyViewports,
+    _frames: LeptosEventReceiver<ViewportFrames>,
+) -> impl IntoView {
+    view! { <canvas id=canvas_id tabindex="0"></canvas> }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn draw_frame(_canvas: &leptos::web_sys::HtmlCanvasElement, _frame: &ViewportFrame) {}
+
+/// Initialize the Bevy app that runs inside a `SceneView` canvas
+#[cfg(target_arch = "wasm32")]
+fn init_scene_app(
+    canvas_id: String,
+    background: Color,
+    plugins: Option<ExtraPlugins>,
+    viewports: BevyViewports,
+    keyboard_focus: BevyEventDuplex<KeyboardFocus>,
+    lifetime: SceneLifetime,
+    runs_winit: bool,
+) -> App {
+    use bevy::asset::AssetMetaCheck;
+    use bevy::log::LogPlugin;
+    use bevy::render::settings::RenderCreation;
+    use bevy::render::{Render, RenderApp, RenderPlugin};
+    use bevy::window::ExitCondition;
+    use bevy::winit::WinitPlugin;
+    use web_sys::wasm_bindgen::JsCast;
+
+    let canvas: Option<web_sys::HtmlCanvasElement> = document()
+        .get_element_by_id(&canvas_id)
+        .and_then(|element| element.dyn_into().ok());
+
+    // Assets are served from the site root, next to the pages that use them.
+    let default_plugins = DefaultPlugins.set(AssetPlugin {
+        file_path: "/".to_string(),
+        meta_check: AssetMetaCheck::Never,
+        ..default()
+    });
+
+    let mut app = App::new();
+    app.insert_non_send_resource(SceneCanvas::new(canvas.clone(), runs_winit));
+    if runs_winit {
+        app.add_plugins(default_plugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                canvas: Some(format!("#{canvas_id}")),
+                fit_canvas_to_parent: true,
+                ..default()
+            }),
+            ..default()
+        }));
+        app.sub_app_mut(RenderApp)
+            .add_systems(Render, share_renderer);
+    } else {
+        let resources = SHARED_RENDERER.with_borrow(|shared| {
+            shared
+                .as_ref()
+                .and_then(|shared| shared.resources.clone())
+                .expect("apps without winit start once the winit app shares its renderer")
+        });
+
+        // Rendered at the size the canvas fills when the app starts, like winit would.
+        let size = canvas
+            .map(|canvas| {
+                canvas
+                    .set_attribute("style", "width: 100%; height: 100%;")
+                    .ok();
+                let scale_factor = window().device_pixel_ratio() as f32;
+                let size = Vec2::new(canvas.client_width() as f32, canvas.client_height() as f32);
+                (size * scale_factor).round().as_uvec2()
+            })
+            .unwrap_or(UVec2::ONE);
+
+        // The log goes to the browser console, which the winit app has set up already.
+        app.add_plugins(
+            default_plugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Manual(resources),
+                    ..default()
+                })
+                .disable::<WinitPlugin>()
+                .disable::<LogPlugin>(),
+        )
+        .insert_resource(OffscreenCanvas { canvas_id, size })
+        .set_runner(run_in_animation_frames);
+    }
+    app.add_plugins((ScenePlugins, SceneLifetimePlugin(lifetime)))
+        .insert_resource(ClearColor(background))
+        .import_event_from_leptos(viewports.commands)
+        .export_event_to_leptos(viewports.frames)
+        .sync_leptos_signal_with_resource(keyboard_focus);
+
+    if let Some(plugins) = plugins {
+        plugins.apply(&mut app);
+    }
+
+    app
+}
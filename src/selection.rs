@@ -0,0 +1,251 @@
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+
+use crate::scene_events::SceneEvent;
+
+const HOVER_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+
+/// Pointer travel in logical pixels after which a press counts as a drag rather than a click.
+const DRAG_THRESHOLD: f32 = 4.0;
+
+/// Marks entities that can be hovered and selected with the pointer.
+#[derive(Component, Default)]
+pub struct Selectable;
+
+/// The currently selected entity, if any.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selection(pub Option<Entity>);
+
+/// The selectable entity currently under the pointer, if any.
+#[derive(Resource, Default)]
+struct Hovered(Option<Entity>);
+
+/// Set while the current pointer press was taken by an editing handle, like a transform gizmo, so
+/// it neither changes the selection nor moves the camera.
+#[derive(Resource, Default)]
+pub struct PointerCaptured(pub bool);
+
+/// Where the last pointer press happened, to tell clicks from camera drags.
+#[derive(Resource, Default)]
+struct PressedAt(Option<Vec2>);
+
+/// Ray-cast selection of `Selectable` entities.
+///
+/// Hovered and selected entities are outlined, and every change of the selection is written as a
+/// `SceneEvent::SelectionChanged`. Clicking empty space clears the selection.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SceneEvent>()
+            .init_resource::<Selection>()
+            .init_resource::<Hovered>()
+            .init_resource::<PressedAt>()
+            .init_resource::<PointerCaptured>()
+            .add_observer(remember_press)
+            .add_observer(select_on_click)
+            .add_observer(hover_over)
+            .add_observer(hover_out)
+            .add_systems(
+                Update,
+                (clear_despawned, report_selection, draw_highlights).chain(),
+            );
+    }
+}
+
+/// Pointer events bubble up to parents and the window, so only the original target is handled.
+fn is_original_target<E: std::fmt::Debug + Clone + Reflect>(trigger: &Trigger<Pointer<E>>) -> bool {
+    trigger.target() == trigger.event().target
+}
+
+fn remember_press(trigger: Trigger<Pointer<Pressed>>, mut pressed_at: ResMut<PressedAt>) {
+    if is_original_target(&trigger) {
+        pressed_at.0 = Some(trigger.event().pointer_location.position);
+    }
+}
+
+fn select_on_click(
+    trigger: Trigger<Pointer<Click>>,
+    selectable: Query<(), With<Selectable>>,
+    pressed_at: Res<PressedAt>,
+    captured: Res<PointerCaptured>,
+    mut selection: ResMut<Selection>,
+) {
+    if !is_original_target(&trigger)
+        || trigger.event().button != PointerButton::Primary
+        || captured.0
+    {
+        return;
+    }
+
+    let position = trigger.event().pointer_location.position;
+    if pressed_at
+        .0
+        .is_some_and(|pressed| pressed.distance(position) > DRAG_THRESHOLD)
+    {
+        return;
+    }
+
+    let entity = trigger.target();
+    selection.set_if_neq(Selection(selectable.contains(entity).then_some(entity)));
+}
+
+fn hover_over(
+    trigger: Trigger<Pointer<Over>>,
+    selectable: Query<(), With<Selectable>>,
+    mut hovered: ResMut<Hovered>,
+) {
+    if is_original_target(&trigger) && selectable.contains(trigger.target()) {
+        hovered.0 = Some(trigger.target());
+    }
+}
+
+fn hover_out(trigger: Trigger<Pointer<Out>>, mut hovered: ResMut<Hovered>) {
+    if hovered.0 == Some(trigger.target()) {
+        hovered.0 = None;
+    }
+}
+
+fn clear_despawned(
+    entities: Query<(), With<Selectable>>,
+    mut selection: ResMut<Selection>,
+    mut hovered: ResMut<Hovered>,
+) {
+    if selection.0.is_some_and(|entity| !entities.contains(entity)) {
+        selection.0 = None;
+    }
+
+    if hovered.0.is_some_and(|entity| !entities.contains(entity)) {
+        hovered.0 = None;
+    }
+}
+
+fn report_selection(selection: Res<Selection>, mut events: EventWriter<SceneEvent>) {
+    if selection.is_changed() && !selection.is_added() {
+        events.write(SceneEvent::SelectionChanged {
+            entity: selection.0,
+        });
+    }
+}
+
+fn draw_highlights(
+    mut gizmos: Gizmos,
+    selection: Res<Selection>,
+    hovered: Res<Hovered>,
+    bounds: Query<(&GlobalTransform, &Aabb), With<Selectable>>,
+) {
+    let mut outline = |entity: Option<Entity>, color: Color| {
+        if let Some((transform, aabb)) = entity.and_then(|entity| bounds.get(entity).ok()) {
+            let local = Affine3A::from_scale_rotation_translation(
+                Vec3::from(aabb.half_extents) * 2.02,
+                Quat::IDENTITY,
+                aabb.center.into(),
+            );
+            gizmos.cuboid(transform.affine() * local, color);
+        }
+    };
+
+    if hovered.0 != selection.0 {
+        outline(hovered.0, HOVER_COLOR);
+    }
+    outline(selection.0, SELECTED_COLOR);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::gizmos::GizmoPlugin;
+    use bevy::math::FloatOrd;
+    use bevy::picking::backend::ray::{RayId, RayMap};
+    use bevy::picking::mesh_picking::{MeshPickingPlugin, MeshPickingSettings};
+    use bevy::picking::pointer::{Location, PointerAction, PointerId, PointerInput};
+    use bevy::picking::{InteractionPlugin, PickSet, PickingPlugin};
+    use bevy::prelude::*;
+    use bevy::render::camera::{ImageRenderTarget, NormalizedRenderTarget};
+    use bevy::render::render_resource::Shader;
+    use bevy::render::view::VisibilityPlugin;
+
+    use super::{Selection, SelectionPlugin};
+    use crate::orbit_camera::OrbitCamera;
+    use crate::scene_description::{SceneDescription, SceneObject};
+    use crate::simulation::headless_app;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    /// Points the mouse from the camera at its focus, where a window would put it.
+    fn aim_at_focus(
+        mut rays: ResMut<RayMap>,
+        camera: Single<(Entity, &GlobalTransform), With<OrbitCamera>>,
+    ) {
+        let (camera, transform) = *camera;
+        rays.map.insert(
+            RayId::new(camera, PointerId::Mouse),
+            Ray3d::new(transform.translation(), transform.forward()),
+        );
+    }
+
+    fn click(app: &mut App) {
+        let location = Location {
+            target: NormalizedRenderTarget::Image(ImageRenderTarget {
+                handle: Handle::default(),
+                scale_factor: FloatOrd(1.0),
+            }),
+            position: Vec2::ZERO,
+        };
+        for action in [
+            PointerAction::Press(PointerButton::Primary),
+            PointerAction::Release(PointerButton::Primary),
+        ] {
+            app.world_mut().send_event(PointerInput::new(
+                PointerId::Mouse,
+                location.clone(),
+                action,
+            ));
+            app.update();
+        }
+    }
+
+    #[test]
+    fn clicking_the_cube_selects_it() {
+        let scene: SceneDescription = ron::from_str(DEFAULT_SCENE).unwrap();
+        let mut app = headless_app(scene);
+        // The highlights are drawn as gizmos, whose plugin brings its shaders along.
+        app.init_asset::<Shader>()
+            .add_plugins((
+                VisibilityPlugin,
+                GizmoPlugin,
+                PickingPlugin::default(),
+                InteractionPlugin,
+                MeshPickingPlugin,
+                SelectionPlugin,
+            ))
+            // Nothing is drawn, so nothing is ever visible in a view.
+            .insert_resource(MeshPickingSettings {
+                ray_cast_visibility: RayCastVisibility::Any,
+                ..default()
+            })
+            .add_systems(
+                PreUpdate,
+                aim_at_focus
+                    .after(PickSet::ProcessInput)
+                    .before(PickSet::Backend),
+            );
+        app.world_mut().spawn(PointerId::Mouse);
+        app.finish();
+        app.cleanup();
+        app.update();
+        app.update();
+
+        click(&mut app);
+
+        let world = app.world_mut();
+        let cube = world
+            .query::<(Entity, &SceneObject)>()
+            .iter(world)
+            .find(|(_, object)| object.id == 0)
+            .map(|(entity, _)| entity);
+        assert!(cube.is_some());
+        assert_eq!(*world.resource::<Selection>(), Selection(cube));
+    }
+}
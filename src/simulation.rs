@@ -0,0 +1,823 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+
+use crate::collaboration::{ObjectSnapshot, OutgoingRoomMessage, RoomMessage, SceneMutation};
+use crate::scene_description::{
+    AlphaModeDescription, CameraDescription, DescribedScene, EnvironmentDescription,
+    LightDescription, MaterialDescription, ObjectDescription, SceneDescription,
+    SceneDescriptionPlugin, SceneObject, SceneReader, TextureSlot, TransformDescription,
+};
+
+/// How often the simulation sends the state of the scene to the room.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far from the origin objects may be moved.
+const MAX_DISTANCE: f32 = 1000.0;
+
+/// How small or large objects may be scaled.
+const SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.001..=1000.0;
+
+/// Longest name of a shared material, in bytes.
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Most objects a loaded scene may have.
+const MAX_OBJECTS: usize = 1000;
+
+/// Most lights a loaded scene may have.
+const MAX_LIGHTS: usize = 32;
+
+/// Widest cone of a spot light, in degrees.
+const MAX_SPOT_ANGLE: f32 = 90.0;
+
+/// A Bevy app without windows or rendering that holds `scene`.
+///
+/// Meshes and materials are kept as assets like in the browser, so the systems working on the
+/// scene run unchanged; nothing is ever drawn. Call `App::finish` and `App::cleanup` once all
+/// plugins are added, then `App::update` to step it.
+pub fn headless_app(scene: SceneDescription) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>()
+        .add_plugins(SceneDescriptionPlugin);
+
+    let scene = app
+        .world_mut()
+        .resource_mut::<Assets<SceneDescription>>()
+        .add(scene);
+    app.world_mut().spawn(DescribedScene(scene));
+
+    app
+}
+
+/// Streams the state of the scene objects to the room as `RoomMessage::Snapshot`s.
+///
+/// Runs next to `CollaborationPlugin` in the server simulation of a room.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OutgoingRoomMessage>()
+            .add_systems(Update, send_snapshots.run_if(on_timer(SNAPSHOT_INTERVAL)));
+    }
+}
+
+fn send_snapshots(
+    mut outgoing: EventWriter<OutgoingRoomMessage>,
+    scene: SceneReader,
+    objects: Query<(Entity, &SceneObject)>,
+) {
+    let objects = objects
+        .iter()
+        .filter_map(|(entity, object)| {
+            let description = scene.object(entity)?;
+            Some(ObjectSnapshot {
+                object: object.id,
+                transform: description.transform,
+                material: description.material,
+            })
+        })
+        .collect();
+
+    outgoing.write(OutgoingRoomMessage(RoomMessage::Snapshot { objects }));
+}
+
+/// Whether the server simulation takes `mutation` from `client`, while it holds the objects with
+/// these ids.
+///
+/// Clients may only spawn objects with new ids of their own, and only edit objects that exist. The
+/// objects of the scene are everyone's, but the ones a client spawned can only be removed or given
+/// a body by that client. Nothing may be moved or scaled out of bounds or get values that aren't
+/// numbers, and loaded scenes are held to the same rules with limits on their size.
+pub fn accepts(client: u32, mutation: &SceneMutation, objects: &HashSet<u64>) -> bool {
+    match mutation {
+        SceneMutation::Transform { object, transform } => {
+            objects.contains(object) && valid_transform(transform)
+        }
+        SceneMutation::Material { object, material } => {
+            objects.contains(object) && valid_material(material)
+        }
+        SceneMutation::Spawn {
+            object,
+            description,
+        } => {
+            owner(*object) == Some(client) && !objects.contains(object) && valid_object(description)
+        }
+        SceneMutation::Body { object, .. } | SceneMutation::Despawn { object } => {
+            objects.contains(object) && owner(*object).is_none_or(|owner| owner == client)
+        }
+        SceneMutation::Load(scene) => valid_scene(scene),
+        SceneMutation::Physics(_) => true,
+    }
+}
+
+/// Keeps the ids of the objects the simulation holds up to date with an accepted `mutation`.
+pub fn track_objects(objects: &mut HashSet<u64>, mutation: &SceneMutation) {
+    match mutation {
+        SceneMutation::Spawn { object, .. } => {
+            objects.insert(*object);
+        }
+        SceneMutation::Despawn { object } => {
+            objects.remove(object);
+        }
+        // Objects of a loaded scene are numbered by their position in it.
+        SceneMutation::Load(scene) => *objects = (0..scene.objects.len() as u64).collect(),
+        SceneMutation::Transform { .. }
+        | SceneMutation::Material { .. }
+        | SceneMutation::Body { .. }
+        | SceneMutation::Physics(_) => {}
+    }
+}
+
+/// The client that spawned the object with this id, or `None` for objects of the scene itself.
+///
+/// See `ObjectIds` for how the ids are handed out.
+fn owner(object: u64) -> Option<u32> {
+    match object >> 32 {
+        0 => None,
+        client => Some((client - 1) as u32),
+    }
+}
+
+fn valid_scene(scene: &SceneDescription) -> bool {
+    scene.objects.len() <= MAX_OBJECTS
+        && scene.lights.len() <= MAX_LIGHTS
+        && scene.objects.iter().all(valid_object)
+        && scene.lights.iter().all(valid_light)
+        && valid_environment(&scene.environment)
+        && valid_camera(&scene.camera)
+}
+
+fn valid_object(object: &ObjectDescription) -> bool {
+    valid_transform(&object.transform) && valid_material(&object.material)
+}
+
+fn valid_environment(environment: &EnvironmentDescription) -> bool {
+    let ambient = environment.ambient.iter().flat_map(|ambient| {
+        let [red, green, blue] = ambient.color;
+        [red, green, blue, ambient.brightness]
+    });
+    let sky = environment.environment_map.iter().flat_map(|sky| {
+        [sky.sky, sky.horizon, sky.ground]
+            .into_iter()
+            .flatten()
+            .chain([sky.intensity])
+    });
+
+    ambient.chain(sky).all(|x| x.is_finite() && x >= 0.0)
+}
+
+fn valid_light(light: &LightDescription) -> bool {
+    let non_negative = |values: &[f32]| values.iter().all(|x| x.is_finite() && *x >= 0.0);
+
+    match light {
+        LightDescription::Point {
+            color,
+            intensity,
+            range,
+            transform,
+            ..
+        } => {
+            non_negative(color) && non_negative(&[*intensity, *range]) && valid_transform(transform)
+        }
+        LightDescription::Directional {
+            color,
+            illuminance,
+            transform,
+            ..
+        } => non_negative(color) && non_negative(&[*illuminance]) && valid_transform(transform),
+        LightDescription::Spot {
+            color,
+            intensity,
+            range,
+            inner_angle,
+            outer_angle,
+            transform,
+            ..
+        } => {
+            non_negative(color)
+                && non_negative(&[*intensity, *range])
+                && (0.0..=MAX_SPOT_ANGLE).contains(outer_angle)
+                && (0.0..=*outer_angle).contains(inner_angle)
+                && valid_transform(transform)
+        }
+    }
+}
+
+/// The camera has to stay in bounds and look somewhere.
+fn valid_camera(camera: &CameraDescription) -> bool {
+    camera
+        .eye
+        .iter()
+        .chain(&camera.focus)
+        .all(|x| x.abs() <= MAX_DISTANCE)
+        && camera.eye != camera.focus
+}
+
+fn valid_transform(transform: &TransformDescription) -> bool {
+    let TransformDescription {
+        translation,
+        rotation,
+        scale,
+    } = transform;
+
+    translation.iter().all(|x| x.abs() <= MAX_DISTANCE)
+        && rotation.iter().all(|x| x.is_finite())
+        && scale.iter().all(|x| SCALE_RANGE.contains(x))
+}
+
+fn valid_material(material: &MaterialDescription) -> bool {
+    let MaterialDescription {
+        name,
+        base_color,
+        metallic,
+        perceptual_roughness,
+        reflectance,
+        emissive,
+        alpha_mode,
+        textures,
+    } = material;
+
+    name.as_ref()
+        .is_none_or(|name| name.len() <= MAX_NAME_LENGTH)
+        && base_color.iter().chain(emissive).all(|x| x.is_finite())
+        && (0.0..=1.0).contains(metallic)
+        && (0.0..=1.0).contains(perceptual_roughness)
+        && (0.0..=1.0).contains(reflectance)
+        && !matches!(alpha_mode, Some(AlphaModeDescription::Mask(cutoff)) if !cutoff.is_finite())
+        && TextureSlot::ALL
+            .iter()
+            .filter_map(|slot| textures.get(*slot).as_deref())
+            .all(valid_asset_path)
+}
+
+/// Textures have to be assets of the site, not files elsewhere on the server.
+fn valid_asset_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.contains("://")
+        && !path.starts_with('/')
+        && !path.contains('\\')
+        && path.split('/').all(|part| part != "..")
+}
+
+/// -------- Server Simulation --------
+#[cfg(feature = "ssr")]
+pub mod server {
+    use std::collections::HashSet;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use bevy::prelude::*;
+    use tokio::sync::mpsc::error::TryRecvError;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+    use super::{accepts, headless_app, track_objects, SimulationPlugin};
+    use crate::collaboration::relay::Rooms;
+    use crate::collaboration::{
+        CollaborationPlugin, IncomingRoomMessage, OutgoingRoomMessage, RoomMessage,
+    };
+    use crate::physics::PhysicsPlugin;
+    use crate::scene_description::{SceneDescription, SceneObject};
+
+    /// How long one step of the simulation takes at least.
+    const TICK: Duration = Duration::from_micros(33_333);
+
+    /// Runs the simulation of `room` on its own thread, as member `client` of the room.
+    ///
+    /// Returns the channel the relay passes the messages of the room to; the simulation stops when
+    /// it is dropped.
+    pub(crate) fn start(
+        rooms: Rooms,
+        room: &str,
+        client: u32,
+        scene: SceneDescription,
+    ) -> UnboundedSender<String> {
+        let (sender, mut receiver) = unbounded_channel::<String>();
+        let room = room.to_string();
+
+        thread::Builder::new()
+            .name(format!("simulation of {room}"))
+            .spawn(move || {
+                let mut simulation = Simulation::new(rooms, room, client, scene);
+
+                loop {
+                    let started = Instant::now();
+
+                    loop {
+                        match receiver.try_recv() {
+                            Ok(text) => simulation.receive(&text),
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => return,
+                        }
+                    }
+                    simulation.update();
+
+                    thread::sleep(TICK.saturating_sub(started.elapsed()));
+                }
+            })
+            .expect("the simulation thread can be spawned");
+
+        sender
+    }
+
+    /// The headless app holding the scene of a room, with its physics played by the room.
+    struct Simulation {
+        app: App,
+        rooms: Rooms,
+        room: String,
+        client: u32,
+        /// The ids of the objects in the app, with the mutations accepted since the last update.
+        objects: HashSet<u64>,
+    }
+
+    impl Simulation {
+        fn new(rooms: Rooms, room: String, client: u32, scene: SceneDescription) -> Self {
+            let mut app = headless_app(scene);
+            app.add_plugins((
+                CollaborationPlugin { room: room.clone() },
+                SimulationPlugin,
+                PhysicsPlugin,
+            ));
+            app.finish();
+            app.cleanup();
+            app.world_mut()
+                .send_event(IncomingRoomMessage(RoomMessage::Welcome { client }));
+
+            let mut simulation = Self {
+                app,
+                rooms,
+                room,
+                client,
+                objects: HashSet::new(),
+            };
+            // Spawns the scene, so that edits of its objects are accepted from the start.
+            simulation.update();
+            simulation
+        }
+
+        /// Takes a message relayed to the simulation, and passes on the mutations it accepts.
+        fn receive(&mut self, text: &str) {
+            let Ok(message) = serde_json::from_str::<RoomMessage>(text) else {
+                return;
+            };
+            if let RoomMessage::Mutation {
+                client: author,
+                mutation,
+            } = &message
+            {
+                if !accepts(*author, mutation, &self.objects) {
+                    return;
+                }
+                track_objects(&mut self.objects, mutation);
+                // Everyone else hears about accepted edits right away.
+                self.rooms.relay(&self.room, self.client, text);
+            }
+            self.app
+                .world_mut()
+                .send_event(IncomingRoomMessage(message));
+        }
+
+        /// Steps the app and sends what it has for the room.
+        fn update(&mut self) {
+            self.app.update();
+
+            let outgoing: Vec<_> = self
+                .app
+                .world_mut()
+                .resource_mut::<Events<OutgoingRoomMessage>>()
+                .drain()
+                .collect();
+            for OutgoingRoomMessage(message) in outgoing {
+                if let Ok(text) = serde_json::to_string(&message) {
+                    self.rooms.relay(&self.room, self.client, &text);
+                }
+            }
+
+            self.objects = self
+                .app
+                .world_mut()
+                .query::<&SceneObject>()
+                .iter(self.app.world())
+                .map(|object| object.id)
+                .collect();
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::Duration;
+
+        use bevy::prelude::*;
+        use bevy::time::TimeUpdateStrategy;
+
+        use super::Simulation;
+        use crate::collaboration::relay::Rooms;
+        use crate::collaboration::{OutgoingRoomMessage, RoomMessage, SceneMutation};
+        use crate::physics::{Playback, TIME_STEP};
+        use crate::scene_description::{RigidBody, SceneDescription};
+
+        /// Sends `playback` to the simulation like a client of the room would.
+        fn play(simulation: &mut Simulation, playback: Playback) {
+            let message = RoomMessage::Mutation {
+                client: 1,
+                mutation: Box::new(SceneMutation::Physics(playback)),
+            };
+            simulation.receive(&serde_json::to_string(&message).unwrap());
+        }
+
+        /// The heights of the cube in the snapshots sent during a few steps.
+        fn streamed_heights(simulation: &mut Simulation) -> Vec<f32> {
+            (0..12)
+                .flat_map(|_| {
+                    simulation.app.update();
+                    simulation
+                        .app
+                        .world_mut()
+                        .resource_mut::<Events<OutgoingRoomMessage>>()
+                        .drain()
+                        .collect::<Vec<_>>()
+                })
+                .filter_map(|OutgoingRoomMessage(message)| match message {
+                    RoomMessage::Snapshot { objects } => objects
+                        .iter()
+                        .find(|snapshot| snapshot.object == 0)
+                        .map(|snapshot| snapshot.transform.translation[1]),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        #[test]
+        fn the_room_plays_the_physics_and_gets_the_bodies_streamed() {
+            let mut scene =
+                SceneDescription::from_ron(include_str!("../public/scenes/default.scene.ron"))
+                    .unwrap();
+            scene.objects[0].transform.translation[1] = 3.0;
+            scene.objects[0].body = Some(RigidBody::Dynamic);
+            let mut simulation = Simulation::new(Rooms::default(), "test".to_string(), 0, scene);
+            simulation
+                .app
+                .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                    TIME_STEP,
+                )));
+
+            let resting = streamed_heights(&mut simulation);
+            assert!(!resting.is_empty());
+            assert!(resting.iter().all(|height| *height == 3.0), "{resting:?}");
+
+            play(&mut simulation, Playback::Play);
+            let falling = streamed_heights(&mut simulation);
+            assert!(falling.len() > 1);
+            assert!(falling[0] < 3.0);
+            assert!(
+                falling.windows(2).all(|pair| pair[1] < pair[0]),
+                "{falling:?}"
+            );
+
+            play(&mut simulation, Playback::Pause);
+            let paused = streamed_heights(&mut simulation);
+            assert!(
+                paused.windows(2).all(|pair| pair[1] == pair[0]),
+                "{paused:?}"
+            );
+
+            play(&mut simulation, Playback::Step);
+            let stepped = streamed_heights(&mut simulation);
+            assert!(stepped[0] < paused[paused.len() - 1], "{stepped:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{
+        accepts, track_objects, valid_material, valid_transform, MAX_LIGHTS, MAX_NAME_LENGTH,
+        MAX_OBJECTS,
+    };
+    use crate::collaboration::SceneMutation;
+    use crate::scene_description::{
+        AlphaModeDescription, CameraDescription, LightDescription, MaterialDescription,
+        MeshDescription, ObjectDescription, RigidBody, SceneDescription, TextureSlot,
+        TransformDescription,
+    };
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    fn sphere(transform: TransformDescription) -> ObjectDescription {
+        ObjectDescription {
+            name: None,
+            mesh: MeshDescription::Sphere { radius: 0.5 },
+            material: MaterialDescription::default(),
+            transform,
+            roles: Vec::new(),
+            body: None,
+        }
+    }
+
+    #[test]
+    fn transforms_stay_within_bounds() {
+        let edge = TransformDescription {
+            translation: [1000.0, -1000.0, 0.0],
+            rotation: [720.0, -90.0, 0.0],
+            scale: [0.001, 1000.0, 1.0],
+        };
+        assert!(valid_transform(&TransformDescription::default()));
+        assert!(valid_transform(&edge));
+
+        let out_of_bounds = [
+            [1000.5, 0.0, 0.0],
+            [0.0, -2000.0, 0.0],
+            [0.0, 0.0, f32::NAN],
+            [f32::INFINITY, 0.0, 0.0],
+        ];
+        for translation in out_of_bounds {
+            let transform = TransformDescription {
+                translation,
+                ..edge
+            };
+            assert!(!valid_transform(&transform), "{translation:?}");
+        }
+
+        for rotation in [[f32::NAN, 0.0, 0.0], [0.0, f32::NEG_INFINITY, 0.0]] {
+            let transform = TransformDescription { rotation, ..edge };
+            assert!(!valid_transform(&transform), "{rotation:?}");
+        }
+
+        let bad_scales = [
+            [0.0, 1.0, 1.0],
+            [1.0, -1.0, 1.0],
+            [1.0, 1.0, 1000.5],
+            [f32::NAN, 1.0, 1.0],
+        ];
+        for scale in bad_scales {
+            let transform = TransformDescription { scale, ..edge };
+            assert!(!valid_transform(&transform), "{scale:?}");
+        }
+    }
+
+    #[test]
+    fn materials_have_short_names_and_values_in_range() {
+        let named = |length| MaterialDescription {
+            name: Some("m".repeat(length)),
+            ..Default::default()
+        };
+        assert!(valid_material(&MaterialDescription::default()));
+        assert!(valid_material(&named(MAX_NAME_LENGTH)));
+        assert!(!valid_material(&named(MAX_NAME_LENGTH + 1)));
+
+        let invalid = [
+            MaterialDescription {
+                base_color: [f32::NAN, 1.0, 1.0, 1.0],
+                ..Default::default()
+            },
+            MaterialDescription {
+                emissive: [0.0, f32::INFINITY, 0.0],
+                ..Default::default()
+            },
+            MaterialDescription {
+                metallic: 1.5,
+                ..Default::default()
+            },
+            MaterialDescription {
+                perceptual_roughness: -0.1,
+                ..Default::default()
+            },
+            MaterialDescription {
+                reflectance: f32::NAN,
+                ..Default::default()
+            },
+            MaterialDescription {
+                alpha_mode: Some(AlphaModeDescription::Mask(f32::NAN)),
+                ..Default::default()
+            },
+        ];
+        for material in invalid {
+            assert!(!valid_material(&material), "{material:?}");
+        }
+    }
+
+    #[test]
+    fn textures_are_assets_of_the_site() {
+        let textured = |path: &str| {
+            let mut material = MaterialDescription::default();
+            *material.textures.get_mut(TextureSlot::NormalMap) = Some(path.to_string());
+            material
+        };
+        assert!(valid_material(&textured("textures/brick_normal.png")));
+
+        for path in [
+            "",
+            "../secrets.png",
+            "textures/../../secrets.png",
+            "/etc/passwd",
+            "https://example.com/texture.png",
+            "textures\\brick.png",
+        ] {
+            assert!(!valid_material(&textured(path)), "{path:?}");
+        }
+    }
+
+    #[test]
+    fn mutations_are_checked_before_they_are_accepted() {
+        let far = TransformDescription {
+            translation: [0.0, 5000.0, 0.0],
+            ..Default::default()
+        };
+        let objects = HashSet::from([0]);
+        assert!(accepts(
+            3,
+            &SceneMutation::Transform {
+                object: 0,
+                transform: TransformDescription::default(),
+            },
+            &objects
+        ));
+        assert!(!accepts(
+            3,
+            &SceneMutation::Transform {
+                object: 0,
+                transform: far,
+            },
+            &objects
+        ));
+        assert!(!accepts(
+            3,
+            &SceneMutation::Transform {
+                object: 1,
+                transform: TransformDescription::default(),
+            },
+            &objects
+        ));
+        assert!(!accepts(
+            3,
+            &SceneMutation::Material {
+                object: 0,
+                material: MaterialDescription {
+                    name: Some("m".repeat(MAX_NAME_LENGTH + 1)),
+                    ..Default::default()
+                },
+            },
+            &objects
+        ));
+
+        // Clients spawn objects with ids of their own only, see `ObjectIds`.
+        let own = (4 << 32) | 1;
+        let spawn = |object, transform| SceneMutation::Spawn {
+            object,
+            description: sphere(transform),
+        };
+        assert!(accepts(
+            3,
+            &spawn(own, TransformDescription::default()),
+            &objects
+        ));
+        assert!(!accepts(
+            3,
+            &spawn((5 << 32) | 1, TransformDescription::default()),
+            &objects
+        ));
+        assert!(!accepts(
+            3,
+            &spawn(0, TransformDescription::default()),
+            &objects
+        ));
+        assert!(!accepts(3, &spawn(own, far), &objects));
+        assert!(!accepts(
+            3,
+            &spawn(own, TransformDescription::default()),
+            &HashSet::from([own])
+        ));
+    }
+
+    #[test]
+    fn only_spawners_remove_their_objects() {
+        let own = (4 << 32) | 1;
+        let others = (5 << 32) | 1;
+        let objects = HashSet::from([0, own, others]);
+
+        for object in [0, own] {
+            assert!(accepts(3, &SceneMutation::Despawn { object }, &objects));
+            assert!(accepts(
+                3,
+                &SceneMutation::Body {
+                    object,
+                    body: Some(RigidBody::Dynamic),
+                },
+                &objects
+            ));
+        }
+        for object in [others, 1, (4 << 32) | 2] {
+            assert!(!accepts(3, &SceneMutation::Despawn { object }, &objects));
+            assert!(!accepts(
+                3,
+                &SceneMutation::Body { object, body: None },
+                &objects
+            ));
+        }
+
+        let mut objects = objects;
+        track_objects(&mut objects, &SceneMutation::Despawn { object: own });
+        assert!(!accepts(
+            3,
+            &SceneMutation::Despawn { object: own },
+            &objects
+        ));
+        track_objects(
+            &mut objects,
+            &SceneMutation::Load(SceneDescription::from_ron(DEFAULT_SCENE).unwrap()),
+        );
+        assert_eq!(objects, HashSet::from([0]));
+    }
+
+    #[test]
+    fn loaded_scenes_have_valid_lights_and_cameras() {
+        let scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        let loads = |scene: &SceneDescription| {
+            accepts(3, &SceneMutation::Load(scene.clone()), &HashSet::new())
+        };
+        assert!(loads(&scene));
+
+        let spot = |inner_angle, outer_angle| LightDescription::Spot {
+            color: [1.0; 3],
+            intensity: 1000.0,
+            range: 20.0,
+            inner_angle,
+            outer_angle,
+            shadows: false,
+            transform: TransformDescription::default(),
+        };
+        let with_light = |light| SceneDescription {
+            lights: vec![light],
+            ..scene.clone()
+        };
+        assert!(loads(&with_light(spot(20.0, 45.0))));
+
+        let invalid_lights = [
+            spot(50.0, 45.0),
+            spot(-1.0, 45.0),
+            spot(20.0, 120.0),
+            spot(f32::NAN, 45.0),
+            LightDescription::Point {
+                color: [1.0; 3],
+                intensity: -1.0,
+                range: 20.0,
+                shadows: false,
+                transform: TransformDescription::default(),
+            },
+            LightDescription::Point {
+                color: [1.0, f32::NAN, 1.0],
+                intensity: 1000.0,
+                range: 20.0,
+                shadows: false,
+                transform: TransformDescription::default(),
+            },
+            LightDescription::Directional {
+                color: [1.0; 3],
+                illuminance: f32::INFINITY,
+                shadows: false,
+                transform: TransformDescription::default(),
+            },
+            LightDescription::Directional {
+                color: [1.0; 3],
+                illuminance: 1000.0,
+                shadows: false,
+                transform: TransformDescription {
+                    translation: [0.0, 5000.0, 0.0],
+                    ..Default::default()
+                },
+            },
+        ];
+        for light in invalid_lights {
+            assert!(!loads(&with_light(light.clone())), "{light:?}");
+        }
+
+        let with_camera = |eye, focus| SceneDescription {
+            camera: CameraDescription { eye, focus },
+            ..scene.clone()
+        };
+        for (eye, focus) in [
+            ([1.0, 1.0, 1.0], [1.0, 1.0, 1.0]),
+            ([f32::NAN, 1.0, 1.0], [0.0; 3]),
+            ([0.0, 5000.0, 0.0], [0.0; 3]),
+        ] {
+            assert!(!loads(&with_camera(eye, focus)), "{eye:?} {focus:?}");
+        }
+
+        let crowded = SceneDescription {
+            lights: vec![spot(20.0, 45.0); MAX_LIGHTS + 1],
+            ..scene.clone()
+        };
+        assert!(!loads(&crowded));
+        let crowded = SceneDescription {
+            objects: vec![sphere(TransformDescription::default()); MAX_OBJECTS + 1],
+            ..scene
+        };
+        assert!(!loads(&crowded));
+    }
+}
@@ -0,0 +1,495 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::render::camera::{CameraProjection, Exposure};
+
+use crate::scene_description::{
+    EnvironmentDescription, LightDescription, MaterialDescription, ObjectDescription,
+    SceneDescription,
+};
+
+/// Size of scene thumbnails in pixels.
+pub const THUMBNAIL_SIZE: UVec2 = UVec2::new(320, 180);
+
+/// Samples per pixel along each axis.
+const SUPERSAMPLING: u32 = 2;
+
+/// Draws `scene` from its camera without a GPU, as RGBA pixels row by row.
+///
+/// Follows the units Bevy lights the scene with, with the default exposure, but only diffuse
+/// lighting, without shadows or tonemapping. Good enough to recognise a scene.
+pub fn render_thumbnail(scene: &SceneDescription, size: UVec2) -> Vec<u8> {
+    let mut raster = Raster::new(size * SUPERSAMPLING);
+
+    let projection = PerspectiveProjection {
+        aspect_ratio: size.x as f32 / size.y as f32,
+        ..default()
+    };
+    let view = Mat4::look_at_rh(scene.camera.eye.into(), scene.camera.focus.into(), Vec3::Y);
+    let clip_from_world = projection.get_clip_from_view() * view;
+
+    for object in &scene.objects {
+        raster.draw(object, scene, clip_from_world, projection.near);
+    }
+
+    raster.downsample(SUPERSAMPLING)
+}
+
+/// A vertex on the screen, with its depth and lit color.
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    position: Vec3,
+    color: Vec3,
+}
+
+struct Raster {
+    size: UVec2,
+    /// Linear colors.
+    colors: Vec<Vec3>,
+    /// Reversed depth like Bevy uses, so larger is closer.
+    depths: Vec<f32>,
+}
+
+impl Raster {
+    fn new(size: UVec2) -> Self {
+        let background = LinearRgba::from(ClearColor::default().0).to_vec3();
+        let pixels = (size.x * size.y) as usize;
+
+        Self {
+            size,
+            colors: vec![background; pixels],
+            depths: vec![0.0; pixels],
+        }
+    }
+
+    fn draw(
+        &mut self,
+        object: &ObjectDescription,
+        scene: &SceneDescription,
+        clip_from_world: Mat4,
+        near: f32,
+    ) {
+        let mesh = Mesh::from(object.mesh);
+        let (Some(positions), Some(normals)) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+                .and_then(|values| values.as_float3()),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+                .and_then(|values| values.as_float3()),
+        ) else {
+            return;
+        };
+
+        let world_from_local = Transform::from(object.transform).compute_matrix();
+        let normal_matrix = Mat3::from_mat4(world_from_local.inverse().transpose());
+        let alpha = object.material.base_color[3];
+
+        let vertices: Vec<Option<ScreenVertex>> = positions
+            .iter()
+            .zip(normals)
+            .map(|(&position, &normal)| {
+                let world = world_from_local.transform_point3(position.into());
+                let clip = clip_from_world * world.extend(1.0);
+                // Triangles reaching behind the camera are left out rather than clipped.
+                if clip.w < near {
+                    return None;
+                }
+
+                let ndc = clip.truncate() / clip.w;
+                let normal = (normal_matrix * Vec3::from(normal)).normalize_or_zero();
+                Some(ScreenVertex {
+                    position: Vec3::new(
+                        (ndc.x + 1.0) / 2.0 * self.size.x as f32,
+                        (1.0 - ndc.y) / 2.0 * self.size.y as f32,
+                        ndc.z,
+                    ),
+                    color: shade(world, normal, &object.material, scene),
+                })
+            })
+            .collect();
+
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..vertices.len()).collect(),
+        };
+        for triangle in indices.chunks_exact(3) {
+            if let (Some(a), Some(b), Some(c)) = (
+                vertices[triangle[0]],
+                vertices[triangle[1]],
+                vertices[triangle[2]],
+            ) {
+                self.fill(a, b, c, alpha);
+            }
+        }
+    }
+
+    fn fill(&mut self, a: ScreenVertex, b: ScreenVertex, c: ScreenVertex, alpha: f32) {
+        let edge = |from: Vec3, to: Vec3, point: Vec2| {
+            (to.x - from.x) * (point.y - from.y) - (to.y - from.y) * (point.x - from.x)
+        };
+
+        // Counter-clockwise front faces turn clockwise once y points down.
+        let area = edge(a.position, b.position, c.position.truncate());
+        if area >= 0.0 {
+            return;
+        }
+
+        let min = a
+            .position
+            .min(b.position)
+            .min(c.position)
+            .truncate()
+            .floor();
+        let max = a.position.max(b.position).max(c.position).truncate().ceil();
+        let min = min.max(Vec2::ZERO).as_uvec2();
+        let max = max.min(self.size.as_vec2()).as_uvec2();
+
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let point = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let weights = Vec3::new(
+                    edge(b.position, c.position, point),
+                    edge(c.position, a.position, point),
+                    edge(a.position, b.position, point),
+                ) / area;
+                if weights.min_element() < 0.0 {
+                    continue;
+                }
+
+                let depth = weights.dot(Vec3::new(a.position.z, b.position.z, c.position.z));
+                let pixel = (y * self.size.x + x) as usize;
+                if depth <= self.depths[pixel] {
+                    continue;
+                }
+
+                let color = a.color * weights.x + b.color * weights.y + c.color * weights.z;
+                self.depths[pixel] = depth;
+                self.colors[pixel] = self.colors[pixel].lerp(color, alpha);
+            }
+        }
+    }
+
+    /// Averages blocks of `factor` by `factor` pixels into sRGB bytes.
+    fn downsample(&self, factor: u32) -> Vec<u8> {
+        let size = self.size / factor;
+        let mut pixels = Vec::with_capacity((size.x * size.y * 4) as usize);
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let mut sum = Vec3::ZERO;
+                for sample_y in y * factor..(y + 1) * factor {
+                    for sample_x in x * factor..(x + 1) * factor {
+                        sum += self.colors[(sample_y * self.size.x + sample_x) as usize];
+                    }
+                }
+                let color = sum / (factor * factor) as f32;
+                pixels.extend_from_slice(&Srgba::from(LinearRgba::from_vec3(color)).to_u8_array());
+            }
+        }
+
+        pixels
+    }
+}
+
+/// The linear color of a point of a surface lit by the lights and environment of `scene`.
+fn shade(
+    position: Vec3,
+    normal: Vec3,
+    material: &MaterialDescription,
+    scene: &SceneDescription,
+) -> Vec3 {
+    let [red, green, blue, _] = material.base_color;
+    let [emissive_red, emissive_green, emissive_blue] = material.emissive;
+    let linear = |red, green, blue| LinearRgba::from(Color::srgb(red, green, blue)).to_vec3();
+    let albedo = linear(red, green, blue) * (1.0 - material.metallic);
+
+    let mut light = environment_light(&scene.environment, normal);
+    for description in &scene.lights {
+        light += incoming_light(description, position, normal);
+    }
+
+    let exposure = Exposure::default().exposure();
+    (albedo * light + linear(emissive_red, emissive_green, emissive_blue)) * exposure
+}
+
+/// Light arriving at a surface from all around, facing `normal`.
+///
+/// The environment map is taken as a sky that is brighter above for surfaces facing up.
+fn environment_light(environment: &EnvironmentDescription, normal: Vec3) -> Vec3 {
+    let linear =
+        |[red, green, blue]: [f32; 3]| LinearRgba::from(Color::srgb(red, green, blue)).to_vec3();
+
+    let ambient = environment.ambient.map_or(Vec3::ZERO, |ambient| {
+        linear(ambient.color) * ambient.brightness
+    });
+    let sky = environment.environment_map.map_or(Vec3::ZERO, |sky| {
+        let up = normal.y * 0.5 + 0.5;
+        linear(sky.ground)
+            .lerp(linear(sky.sky), up)
+            .lerp(linear(sky.horizon), 0.5)
+            * sky.intensity
+    });
+
+    ambient + sky
+}
+
+/// Light arriving at a surface from one light, with the Lambertian falloff.
+fn incoming_light(description: &LightDescription, position: Vec3, normal: Vec3) -> Vec3 {
+    let linear = |[red, green, blue]: [f32; 3]| LinearRgba::from(Color::srgb(red, green, blue));
+
+    match *description {
+        LightDescription::Point {
+            color,
+            intensity,
+            range,
+            transform,
+            ..
+        } => {
+            let to_light = Vec3::from(transform.translation) - position;
+            let lambert = normal.dot(to_light.normalize_or_zero()).max(0.0) / PI;
+            linear(color).to_vec3() * intensity / (4.0 * PI)
+                * distance_attenuation(to_light, range)
+                * lambert
+        }
+        LightDescription::Directional {
+            color,
+            illuminance,
+            transform,
+            ..
+        } => {
+            let direction = Transform::from(transform).forward();
+            let lambert = normal.dot(-*direction).max(0.0) / PI;
+            linear(color).to_vec3() * illuminance * lambert
+        }
+        LightDescription::Spot {
+            color,
+            intensity,
+            range,
+            inner_angle,
+            outer_angle,
+            transform,
+            ..
+        } => {
+            let transform = Transform::from(transform);
+            let to_light = transform.translation - position;
+            let to_light_direction = to_light.normalize_or_zero();
+            let lambert = normal.dot(to_light_direction).max(0.0) / PI;
+
+            let (cos_inner, cos_outer) = (
+                inner_angle.to_radians().cos(),
+                outer_angle.to_radians().cos(),
+            );
+            let scale = 1.0 / (cos_inner - cos_outer).max(1e-4);
+            let cone = transform.forward().dot(-to_light_direction);
+            let spot = ((cone - cos_outer) * scale).clamp(0.0, 1.0).powi(2);
+
+            linear(color).to_vec3() * intensity / (4.0 * PI)
+                * distance_attenuation(to_light, range)
+                * spot
+                * lambert
+        }
+    }
+}
+
+/// Inverse-square falloff that fades out smoothly at `range`, as in Bevy's shaders.
+fn distance_attenuation(to_light: Vec3, range: f32) -> f32 {
+    let distance_squared = to_light.length_squared();
+    let factor = distance_squared / (range * range);
+    let smooth = (1.0 - factor * factor).clamp(0.0, 1.0);
+    smooth * smooth / distance_squared.max(1e-4)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{render_thumbnail, THUMBNAIL_SIZE};
+    use crate::scene_description::SceneDescription;
+
+    const DEFAULT_SCENE: &str = include_str!("../public/scenes/default.scene.ron");
+
+    #[test]
+    fn the_default_cube_is_drawn_over_the_background() {
+        let scene = SceneDescription::from_ron(DEFAULT_SCENE).unwrap();
+        let pixels = render_thumbnail(&scene, THUMBNAIL_SIZE);
+        assert_eq!(
+            pixels.len(),
+            (THUMBNAIL_SIZE.x * THUMBNAIL_SIZE.y * 4) as usize
+        );
+
+        let pixel = |x: u32, y: u32| {
+            let start = ((y * THUMBNAIL_SIZE.x + x) * 4) as usize;
+            <[u8; 4]>::try_from(&pixels[start..start + 4]).unwrap()
+        };
+        let background = Srgba::from(ClearColor::default().0).to_u8_array();
+        let (right, bottom) = (THUMBNAIL_SIZE.x - 1, THUMBNAIL_SIZE.y - 1);
+        for corner in [(0, 0), (right, 0), (0, bottom), (right, bottom)] {
+            assert_eq!(pixel(corner.0, corner.1), background, "{corner:?}");
+        }
+
+        // The camera looks at the middle of the bottom of the blue cube.
+        let [red, green, blue, alpha] = pixel(THUMBNAIL_SIZE.x / 2, THUMBNAIL_SIZE.y / 2);
+        assert_ne!([red, green, blue, alpha], background);
+        assert!(blue > green && green > red, "{:?}", [red, green, blue]);
+        assert_eq!(alpha, 255);
+    }
+}
+
+/// -------- Thumbnail Cache --------
+#[cfg(feature = "ssr")]
+pub mod cache {
+    use std::io::{Cursor, Error, ErrorKind};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use axum::extract::{Path, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use image::{ImageFormat, RgbaImage};
+    use tokio::fs;
+
+    use super::{render_thumbnail, THUMBNAIL_SIZE};
+    use crate::scene_storage::store::{is_valid_id, SceneStore};
+
+    /// Renders thumbnails of stored scenes once and keeps them as `<id>.png` files in a directory.
+    ///
+    /// Stored scenes never change, so a thumbnail stays valid for as long as its scene exists.
+    #[derive(Clone, Debug)]
+    pub struct ThumbnailCache {
+        dir: PathBuf,
+        scenes: SceneStore,
+    }
+
+    impl ThumbnailCache {
+        pub fn new(dir: impl Into<PathBuf>, scenes: SceneStore) -> Self {
+            Self {
+                dir: dir.into(),
+                scenes,
+            }
+        }
+
+        /// Uses the directory in `THUMBNAIL_DIR`, or `data/thumbnails`.
+        pub fn from_env(scenes: SceneStore) -> Self {
+            Self::new(
+                std::env::var("THUMBNAIL_DIR").unwrap_or_else(|_| "data/thumbnails".to_string()),
+                scenes,
+            )
+        }
+
+        /// The PNG thumbnail of the stored scene with this id, or `None` if there is none.
+        pub async fn get(&self, id: &str) -> Result<Option<Vec<u8>>, Error> {
+            // Ids come from the client, and anything that could leave the directory names no scene.
+            if !is_valid_id(id) {
+                return Ok(None);
+            }
+
+            let scene = match self.scenes.load(id).await {
+                Ok(Some(scene)) => scene,
+                Err(err) => return Err(err),
+                Ok(None) => {
+                    // The scene was deleted, so its thumbnail goes too.
+                    fs::remove_file(self.dir.join(format!("{id}.png")))
+                        .await
+                        .ok();
+                    return Ok(None);
+                }
+            };
+
+            let path = self.dir.join(format!("{id}.png"));
+            match fs::read(&path).await {
+                Ok(png) => return Ok(Some(png)),
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                Err(_) => {}
+            }
+
+            let png = tokio::task::spawn_blocking(move || {
+                let pixels = render_thumbnail(&scene, THUMBNAIL_SIZE);
+                let image = RgbaImage::from_raw(THUMBNAIL_SIZE.x, THUMBNAIL_SIZE.y, pixels)
+                    .expect("thumbnails have a pixel for every position");
+                let mut png = Vec::new();
+                image
+                    .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                    .map(|_| png)
+                    .map_err(Error::other)
+            })
+            .await
+            .map_err(Error::other)??;
+
+            // Written next to the thumbnail first, so it is never read half written.
+            static PARTS: AtomicU64 = AtomicU64::new(0);
+            let part = self.dir.join(format!(
+                "{id}.{}.part",
+                PARTS.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&self.dir).await?;
+            fs::write(&part, &png).await?;
+            fs::rename(&part, &path).await?;
+
+            Ok(Some(png))
+        }
+    }
+
+    /// Serves `/thumb/{id}.png`.
+    pub async fn serve(Path(file): Path<String>, State(cache): State<ThumbnailCache>) -> Response {
+        let Some(id) = file.strip_suffix(".png") else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+
+        match cache.get(id).await {
+            Ok(Some(png)) => ([(header::CONTENT_TYPE, "image/png")], png).into_response(),
+            Ok(None) => StatusCode::NOT_FOUND.into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{ThumbnailCache, THUMBNAIL_SIZE};
+        use crate::scene_description::SceneDescription;
+        use crate::scene_storage::store::SceneStore;
+
+        #[tokio::test]
+        async fn saved_scenes_get_a_png_of_thumbnail_size() {
+            let dir = std::env::temp_dir().join(format!("bevytos-pngs-{}", std::process::id()));
+            let scenes = SceneStore::new(dir.join("scenes"));
+            let scene =
+                SceneDescription::from_ron(include_str!("../public/scenes/default.scene.ron"))
+                    .unwrap();
+            let info = scenes.save(&scene).await.unwrap();
+            let cache = ThumbnailCache::new(dir.join("thumbnails"), scenes);
+
+            let png = cache.get(&info.id).await.unwrap();
+            let cached = cache.get(&info.id).await.unwrap();
+            tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+            let png = png.unwrap();
+            assert_eq!(cached.as_ref(), Some(&png));
+            let image = image::load_from_memory(&png).unwrap();
+            assert_eq!(
+                (image.width(), image.height()),
+                (THUMBNAIL_SIZE.x, THUMBNAIL_SIZE.y)
+            );
+        }
+
+        #[tokio::test]
+        async fn ids_leaving_the_directory_name_no_thumbnail() {
+            let dir = std::env::temp_dir().join(format!("bevytos-thumbs-{}", std::process::id()));
+            let cache =
+                ThumbnailCache::new(dir.join("thumbnails"), SceneStore::new(dir.join("scenes")));
+            tokio::fs::create_dir_all(dir.join("thumbnails"))
+                .await
+                .unwrap();
+            tokio::fs::write(dir.join("outside.png"), b"not a thumbnail")
+                .await
+                .unwrap();
+
+            let found = cache.get("../outside").await.unwrap();
+            let outside = tokio::fs::try_exists(dir.join("outside.png"))
+                .await
+                .unwrap();
+            tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+            assert_eq!(found, None);
+            assert!(outside);
+        }
+    }
+}
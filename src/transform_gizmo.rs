@@ -0,0 +1,444 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::history::{EditCommand, SceneEdit};
+use crate::orbit_camera::{OrbitCamera, OrbitCameraSystems};
+use crate::selection::{PointerCaptured, Selection};
+
+/// Size of the gizmo as a share of its distance to the camera, so it keeps its size on screen.
+const GIZMO_SCALE: f32 = 0.15;
+
+/// Distance in logical pixels within which a handle is grabbed.
+const PICK_DISTANCE: f32 = 8.0;
+
+/// Segments the rotation rings are picked along.
+const RING_SEGMENTS: usize = 48;
+
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+
+/// What dragging the handles of the gizmo does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    /// Moves along the world axes.
+    #[default]
+    Translate,
+    /// Turns around the world axes.
+    Rotate,
+    /// Stretches along the axes of the entity.
+    Scale,
+}
+
+/// How the gizmo of the selected entity behaves, kept in sync with a Leptos signal.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct GizmoSettings {
+    pub mode: GizmoMode,
+    /// Whether drags move in steps.
+    pub snap: bool,
+    /// Step in units when translating.
+    pub translate_step: f32,
+    /// Step in degrees when rotating.
+    pub rotate_step: f32,
+    /// Step of the scale when scaling.
+    pub scale_step: f32,
+}
+
+impl Default for GizmoSettings {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::Translate,
+            snap: false,
+            translate_step: 0.25,
+            rotate_step: 15.0,
+            scale_step: 0.1,
+        }
+    }
+}
+
+/// The transform of the selected entity, written when it changes and when the selection does.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct SelectedTransform(pub Option<Transform>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    fn vector(self) -> Vec3 {
+        match self {
+            Axis::X => Vec3::X,
+            Axis::Y => Vec3::Y,
+            Axis::Z => Vec3::Z,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Axis::X => Color::srgb(0.9, 0.2, 0.2),
+            Axis::Y => Color::srgb(0.3, 0.8, 0.2),
+            Axis::Z => Color::srgb(0.2, 0.4, 0.9),
+        }
+    }
+
+    /// Where the handle of this axis points for an entity at `transform`.
+    fn direction(self, mode: GizmoMode, transform: &Transform) -> Vec3 {
+        match mode {
+            GizmoMode::Scale => transform.rotation * self.vector(),
+            GizmoMode::Translate | GizmoMode::Rotate => self.vector(),
+        }
+    }
+}
+
+/// A handle being dragged.
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    axis: Axis,
+    mode: GizmoMode,
+    /// Transform of the entity when the drag started.
+    start: Transform,
+    /// Where the drag started, on the axis or on the plane of the ring.
+    grab: Vec3,
+}
+
+#[derive(Resource, Default)]
+struct GizmoState {
+    hovered: Option<Axis>,
+    drag: Option<Drag>,
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct TransformGizmos;
+
+/// Translate, rotate and scale handles on the selected entity.
+///
+/// Each handle is constrained to one axis, and with `GizmoSettings::snap` drags move in steps.
+/// Grabbing a handle doesn't move the camera or change the selection. Changes of the selected
+/// transform, from the gizmo or anywhere else, are written as `SelectedTransform`.
+///
+/// Entities are expected to sit directly in the scene root, where their transform is global.
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GizmoSettings>()
+            .init_resource::<GizmoState>()
+            .add_event::<SelectedTransform>()
+            // Drawn over the scene, so handles inside meshes stay visible.
+            .insert_gizmo_config(
+                TransformGizmos,
+                GizmoConfig {
+                    depth_bias: -1.0,
+                    line: GizmoLineConfig {
+                        width: 3.0,
+                        ..default()
+                    },
+                    ..default()
+                },
+            )
+            .add_systems(
+                Update,
+                (
+                    (pick_handle, drag_handle)
+                        .chain()
+                        .before(OrbitCameraSystems),
+                    (report_selected_transform, draw_gizmo).after(drag_handle),
+                ),
+            );
+    }
+}
+
+/// The cursor and the camera the gizmo is used through.
+#[derive(SystemParam)]
+struct GizmoView<'w> {
+    window: Single<'w, &'static Window, With<PrimaryWindow>>,
+    camera: Single<'w, (&'static Camera, &'static GlobalTransform), With<OrbitCamera>>,
+}
+
+impl GizmoView<'_> {
+    fn cursor(&self) -> Option<Vec2> {
+        self.window.cursor_position()
+    }
+
+    /// The ray from the camera through the cursor.
+    fn ray(&self) -> Option<Ray3d> {
+        let (camera, camera_transform) = *self.camera;
+        camera
+            .viewport_to_world(camera_transform, self.cursor()?)
+            .ok()
+    }
+}
+
+/// Size of the gizmo of an entity at `position`.
+fn gizmo_size(camera: &GlobalTransform, position: Vec3) -> f32 {
+    camera.translation().distance(position) * GIZMO_SCALE
+}
+
+/// Points along the ring of `axis` around `center`.
+fn ring_points(center: Vec3, axis: Vec3, radius: f32) -> impl Iterator<Item = Vec3> {
+    let u = axis.any_orthonormal_vector();
+    let v = axis.cross(u);
+
+    (0..=RING_SEGMENTS).map(move |segment| {
+        let angle = segment as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+        center + (u * angle.cos() + v * angle.sin()) * radius
+    })
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t =
+        ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
+}
+
+/// The handle under `cursor`, if any.
+fn handle_at(
+    cursor: Vec2,
+    mode: GizmoMode,
+    transform: &Transform,
+    (camera, camera_transform): (&Camera, &GlobalTransform),
+) -> Option<Axis> {
+    let origin = transform.translation;
+    let size = gizmo_size(camera_transform, origin);
+    let project = |point| camera.world_to_viewport(camera_transform, point).ok();
+
+    let distance = |axis: Axis| -> Option<f32> {
+        let direction = axis.direction(mode, transform);
+        let points: Vec<Vec2> = match mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                vec![project(origin)?, project(origin + direction * size)?]
+            }
+            GizmoMode::Rotate => ring_points(origin, direction, size)
+                .map(project)
+                .collect::<Option<_>>()?,
+        };
+
+        points
+            .windows(2)
+            .map(|segment| distance_to_segment(cursor, segment[0], segment[1]))
+            .reduce(f32::min)
+    };
+
+    Axis::ALL
+        .into_iter()
+        .filter_map(|axis| Some((axis, distance(axis)?)))
+        .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(axis, _)| axis)
+}
+
+/// The point of the line through `origin` along `direction` that is closest to `ray`.
+fn closest_on_line(origin: Vec3, direction: Vec3, ray: Ray3d) -> Option<Vec3> {
+    let along = direction.dot(*ray.direction);
+    let denominator = 1.0 - along * along;
+    // Looking straight down the axis leaves no sensible point to drag to.
+    if denominator < 1e-4 {
+        return None;
+    }
+
+    let offset = origin - ray.origin;
+    let t = (along * ray.direction.dot(offset) - direction.dot(offset)) / denominator;
+    Some(origin + direction * t)
+}
+
+/// Where `ray` hits the handle of `axis` of an entity at `transform`: the axis line of the arrows
+/// or the plane of the ring.
+fn grab_point(mode: GizmoMode, axis: Axis, transform: &Transform, ray: Ray3d) -> Option<Vec3> {
+    let origin = transform.translation;
+    let direction = axis.direction(mode, transform);
+
+    match mode {
+        GizmoMode::Translate | GizmoMode::Scale => closest_on_line(origin, direction, ray),
+        GizmoMode::Rotate => {
+            let plane = InfinitePlane3d::new(direction);
+            let distance = ray.intersect_plane(origin, plane)?;
+            Some(ray.get_point(distance))
+        }
+    }
+}
+
+fn snap(value: f32, step: f32, enabled: bool) -> f32 {
+    if enabled && step > 0.0 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+impl Drag {
+    /// The transform the dragged entity gets with the cursor along `ray`.
+    fn apply(&self, settings: &GizmoSettings, ray: Ray3d) -> Option<Transform> {
+        let origin = self.start.translation;
+        let direction = self.axis.direction(self.mode, &self.start);
+        let point = grab_point(self.mode, self.axis, &self.start, ray)?;
+        let mut transform = self.start;
+
+        match self.mode {
+            GizmoMode::Translate => {
+                let distance = (point - self.grab).dot(direction);
+                let distance = snap(distance, settings.translate_step, settings.snap);
+                transform.translation = origin + direction * distance;
+            }
+            GizmoMode::Rotate => {
+                let from = (self.grab - origin).normalize_or_zero();
+                let to = (point - origin).normalize_or_zero();
+                let angle = direction.dot(from.cross(to)).atan2(from.dot(to));
+                let degrees = snap(angle.to_degrees(), settings.rotate_step, settings.snap);
+                transform.rotation =
+                    Quat::from_axis_angle(direction, degrees.to_radians()) * self.start.rotation;
+            }
+            GizmoMode::Scale => {
+                let grabbed = (self.grab - origin).dot(direction);
+                if grabbed.abs() < f32::EPSILON {
+                    return None;
+                }
+
+                let index = self.axis as usize;
+                let factor = (point - origin).dot(direction) / grabbed;
+                let scale = snap(
+                    self.start.scale[index] * factor,
+                    settings.scale_step,
+                    settings.snap,
+                );
+                transform.scale[index] = scale.max(0.01);
+            }
+        }
+
+        Some(transform)
+    }
+}
+
+fn pick_handle(
+    view: GizmoView,
+    buttons: Res<ButtonInput<MouseButton>>,
+    settings: Res<GizmoSettings>,
+    selection: Res<Selection>,
+    transforms: Query<&Transform>,
+    mut state: ResMut<GizmoState>,
+    mut captured: ResMut<PointerCaptured>,
+) {
+    if state.drag.is_some() {
+        return;
+    }
+
+    let target = selection.0.and_then(|entity| transforms.get(entity).ok());
+    state.hovered = match (view.cursor(), target) {
+        (Some(cursor), Some(transform)) => {
+            handle_at(cursor, settings.mode, transform, *view.camera)
+        }
+        _ => None,
+    };
+
+    if buttons.just_pressed(MouseButton::Left) {
+        let drag = match (state.hovered, target, view.ray()) {
+            (Some(axis), Some(transform), Some(ray)) => {
+                grab_point(settings.mode, axis, transform, ray).map(|grab| Drag {
+                    axis,
+                    mode: settings.mode,
+                    start: *transform,
+                    grab,
+                })
+            }
+            _ => None,
+        };
+
+        captured.0 = drag.is_some();
+        state.drag = drag;
+    }
+}
+
+fn drag_handle(
+    view: GizmoView,
+    buttons: Res<ButtonInput<MouseButton>>,
+    settings: Res<GizmoSettings>,
+    selection: Res<Selection>,
+    mut state: ResMut<GizmoState>,
+    transforms: Query<&Transform>,
+    mut edits: EventWriter<EditCommand>,
+) {
+    let Some(drag) = state.drag else {
+        return;
+    };
+
+    let target = selection
+        .0
+        .and_then(|entity| Some((entity, transforms.get(entity).ok()?)));
+    let (true, Some((entity, transform))) = (buttons.pressed(MouseButton::Left), target) else {
+        state.drag = None;
+        return;
+    };
+
+    if let Some(dragged) = view
+        .ray()
+        .and_then(|ray| drag.apply(&settings, ray))
+        .filter(|dragged| dragged != transform)
+    {
+        edits.write(EditCommand {
+            entity,
+            edit: SceneEdit::Transform(dragged),
+        });
+    }
+}
+
+fn report_selected_transform(
+    selection: Res<Selection>,
+    transforms: Query<Ref<Transform>>,
+    mut events: EventWriter<SelectedTransform>,
+) {
+    let transform = selection.0.and_then(|entity| transforms.get(entity).ok());
+
+    if selection.is_changed() || transform.as_ref().is_some_and(Ref::is_changed) {
+        events.write(SelectedTransform(transform.map(|transform| *transform)));
+    }
+}
+
+fn draw_gizmo(
+    mut gizmos: Gizmos<TransformGizmos>,
+    settings: Res<GizmoSettings>,
+    selection: Res<Selection>,
+    state: Res<GizmoState>,
+    transforms: Query<&Transform>,
+    camera: Single<&GlobalTransform, With<OrbitCamera>>,
+) {
+    let Some(transform) = selection.0.and_then(|entity| transforms.get(entity).ok()) else {
+        return;
+    };
+
+    let origin = transform.translation;
+    let size = gizmo_size(&camera, origin);
+    let active = state.drag.map(|drag| drag.axis).or(state.hovered);
+
+    for axis in Axis::ALL {
+        let color = if active == Some(axis) {
+            HIGHLIGHT_COLOR
+        } else {
+            axis.color()
+        };
+        let direction = axis.direction(settings.mode, transform);
+        let tip = origin + direction * size;
+
+        match settings.mode {
+            GizmoMode::Translate => {
+                gizmos.arrow(origin, tip, color);
+            }
+            GizmoMode::Rotate => {
+                let rotation = Quat::from_rotation_arc(Vec3::Z, direction);
+                gizmos.circle(Isometry3d::new(origin, rotation), size, color);
+            }
+            GizmoMode::Scale => {
+                gizmos.line(origin, tip, color);
+                gizmos.cuboid(
+                    Transform::from_translation(tip)
+                        .with_rotation(transform.rotation)
+                        .with_scale(Vec3::splat(size * 0.12)),
+                    color,
+                );
+            }
+        }
+    }
+}
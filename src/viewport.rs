@@ -0,0 +1,259 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::window::WindowRef;
+
+/// Opens and closes extra canvases that show the scene from their own camera.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub enum ViewportCommand {
+    /// Start rendering for the canvas with this id, at `size` pixels.
+    Open {
+        canvas_id: String,
+        camera: Transform,
+        size: UVec2,
+    },
+    /// Stop rendering for the canvas with this id.
+    Close { canvas_id: String },
+}
+
+/// A rendered frame of a viewport, as tightly packed sRGB RGBA rows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewportFrame {
+    pub canvas_id: String,
+    pub size: UVec2,
+    pub pixels: Vec<u8>,
+}
+
+/// The frames of all viewports that finished rendering since the last update.
+#[derive(Event, Clone, Debug, Default, PartialEq)]
+pub struct ViewportFrames(pub Vec<ViewportFrame>);
+
+/// Camera rendering offscreen for the viewport canvas with this id.
+#[derive(Component, Debug)]
+pub struct ViewportCamera(pub String);
+
+/// Reads the image of a `ViewportCamera` back from the GPU.
+#[derive(Component, Debug)]
+struct ViewportReadback {
+    canvas_id: String,
+    size: UVec2,
+}
+
+#[derive(Resource, Default)]
+struct PendingFrames(Vec<ViewportFrame>);
+
+/// Renders the cameras of an app without a window into an image instead, which is read back as
+/// the frames of the canvas with this id.
+#[derive(Resource, Clone, Debug)]
+pub struct OffscreenCanvas {
+    pub canvas_id: String,
+    pub size: UVec2,
+}
+
+/// Handles `ViewportCommand`s and writes the rendered `ViewportFrames`.
+///
+/// Browsers using WebGL can only present a single canvas per app, so viewports render into an
+/// image that is read back and handed to Leptos to draw.
+pub struct ViewportPlugin;
+
+impl Plugin for ViewportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ViewportCommand>()
+            .add_event::<ViewportFrames>()
+            .init_resource::<PendingFrames>()
+            .add_systems(Update, (handle_viewport_commands, target_offscreen_canvas))
+            .add_systems(PostUpdate, write_viewport_frames);
+    }
+}
+
+fn handle_viewport_commands(
+    mut commands: Commands,
+    mut events: EventReader<ViewportCommand>,
+    mut images: ResMut<Assets<Image>>,
+    cameras: Query<(Entity, &ViewportCamera)>,
+    readbacks: Query<(Entity, &ViewportReadback)>,
+) {
+    for event in events.read() {
+        match event {
+            ViewportCommand::Open {
+                canvas_id,
+                camera,
+                size,
+            } => {
+                let size = size.max(UVec2::ONE);
+                let image = readback_image(&mut images, size);
+
+                commands.spawn((
+                    Camera3d::default(),
+                    Camera {
+                        target: RenderTarget::Image(image.clone().into()),
+                        ..default()
+                    },
+                    *camera,
+                    ViewportCamera(canvas_id.clone()),
+                ));
+                spawn_readback(&mut commands, image, canvas_id.clone(), size);
+            }
+            ViewportCommand::Close { canvas_id } => {
+                for (camera, _) in cameras.iter().filter(|(_, c)| c.0 == *canvas_id) {
+                    commands.entity(camera).despawn();
+                }
+                for (readback, _) in readbacks.iter().filter(|(_, r)| r.canvas_id == *canvas_id) {
+                    commands.entity(readback).despawn();
+                }
+            }
+        }
+    }
+}
+
+/// Points the cameras that would draw to the missing primary window at the `OffscreenCanvas`.
+fn target_offscreen_canvas(
+    mut commands: Commands,
+    canvas: Option<Res<OffscreenCanvas>>,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<&mut Camera, Added<Camera>>,
+    mut image: Local<Option<Handle<Image>>>,
+) {
+    let Some(canvas) = canvas else {
+        return;
+    };
+
+    for mut camera in &mut cameras {
+        if !matches!(camera.target, RenderTarget::Window(WindowRef::Primary)) {
+            continue;
+        }
+
+        let image = image.get_or_insert_with(|| {
+            let size = canvas.size.max(UVec2::ONE);
+            let image = readback_image(&mut images, size);
+            spawn_readback(&mut commands, image.clone(), canvas.canvas_id.clone(), size);
+            image
+        });
+        camera.target = RenderTarget::Image(image.clone().into());
+    }
+}
+
+/// Creates an image of `size` pixels that cameras can render into and that can be read back.
+fn readback_image(images: &mut Assets<Image>, size: UVec2) -> Handle<Image> {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage |=
+        TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+
+    images.add(image)
+}
+
+fn spawn_readback(commands: &mut Commands, image: Handle<Image>, canvas_id: String, size: UVec2) {
+    commands
+        .spawn((
+            Readback::texture(image),
+            ViewportReadback { canvas_id, size },
+        ))
+        .observe(collect_frame);
+}
+
+fn collect_frame(
+    trigger: Trigger<ReadbackComplete>,
+    readbacks: Query<&ViewportReadback>,
+    mut pending: ResMut<PendingFrames>,
+) {
+    let Ok(readback) = readbacks.get(trigger.target()) else {
+        return;
+    };
+
+    // Rows of the read back texture are padded to the GPU's copy alignment.
+    let data = &trigger.event().0;
+    let row = readback.size.x as usize * 4;
+    let padded_row = data.len() / readback.size.y as usize;
+    let pixels = data
+        .chunks_exact(padded_row)
+        .flat_map(|padded| &padded[..row])
+        .copied()
+        .collect();
+
+    pending.0.push(ViewportFrame {
+        canvas_id: readback.canvas_id.clone(),
+        size: readback.size,
+        pixels,
+    });
+}
+
+fn write_viewport_frames(
+    mut pending: ResMut<PendingFrames>,
+    mut frames: EventWriter<ViewportFrames>,
+) {
+    if !pending.0.is_empty() {
+        frames.write(ViewportFrames(std::mem::take(&mut pending.0)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::render::camera::RenderTarget;
+
+    use super::{OffscreenCanvas, ViewportPlugin, ViewportReadback};
+
+    #[test]
+    fn cameras_of_windowless_apps_render_to_the_offscreen_canvas() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), ViewportPlugin))
+            .init_asset::<Image>()
+            .insert_resource(OffscreenCanvas {
+                canvas_id: "second_scene".to_string(),
+                size: UVec2::new(320, 240),
+            });
+
+        let image = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(Image::default());
+        let cameras = [
+            app.world_mut().spawn(Camera3d::default()).id(),
+            app.world_mut().spawn(Camera3d::default()).id(),
+        ];
+        let preview = app
+            .world_mut()
+            .spawn((
+                Camera3d::default(),
+                Camera {
+                    target: RenderTarget::Image(image.clone().into()),
+                    ..default()
+                },
+            ))
+            .id();
+        app.update();
+
+        let target = |entity: Entity| app.world().get::<Camera>(entity).unwrap().target.clone();
+        let (RenderTarget::Image(first), RenderTarget::Image(second)) =
+            (target(cameras[0]), target(cameras[1]))
+        else {
+            panic!("the cameras still render to the window");
+        };
+        assert_eq!(first.handle, second.handle);
+        assert_ne!(first.handle, image);
+        assert!(matches!(target(preview), RenderTarget::Image(preview) if preview.handle == image));
+
+        let readbacks: Vec<(String, UVec2)> = app
+            .world_mut()
+            .query::<&ViewportReadback>()
+            .iter(app.world())
+            .map(|readback| (readback.canvas_id.clone(), readback.size))
+            .collect();
+        assert_eq!(
+            readbacks,
+            [("second_scene".to_string(), UVec2::new(320, 240))]
+        );
+    }
+}